@@ -0,0 +1,85 @@
+import java.io.BufferedReader;
+import java.io.ByteArrayOutputStream;
+import java.io.IOException;
+import java.io.InputStreamReader;
+import java.io.OutputStream;
+import java.net.InetAddress;
+import java.net.ServerSocket;
+import java.net.Socket;
+import java.net.SocketTimeoutException;
+import java.nio.charset.StandardCharsets;
+import java.nio.file.FileSystems;
+import java.nio.file.Files;
+import java.nio.file.Path;
+import java.nio.file.Paths;
+import java.nio.file.StandardCopyOption;
+import java.nio.file.attribute.PosixFilePermissions;
+import java.util.UUID;
+import javax.tools.JavaCompiler;
+import javax.tools.ToolProvider;
+
+/**
+ * barista's compile daemon, keeps javac warm so roast doesn't pay for starting a jvm on every build.
+ *
+ * <p>It listens on a random port on localhost and writes the port and a token to the state file it is
+ * given. A request is the token, the number of arguments and then every javac argument on its own line.
+ * The response is {@code exit <code>} followed by everything javac printed. Anyone who can read the token can
+ * compile (and run annotation processors) as this user, so the state file is only readable by the user.
+ */
+public class BaristaCompileDaemon {
+    /** the daemon exits after half an hour without builds */
+    private static final int IDLE_TIMEOUT = 30 * 60 * 1000;
+
+    public static void main(String[] args) throws IOException {
+        Path state = Paths.get(args[0]);
+        JavaCompiler compiler = ToolProvider.getSystemJavaCompiler();
+        String token = UUID.randomUUID().toString();
+        try (ServerSocket server = new ServerSocket(0, 50, InetAddress.getLoopbackAddress())) {
+            server.setSoTimeout(IDLE_TIMEOUT);
+            Path temp = state.resolveSibling(state.getFileName() + ".tmp");
+            Files.deleteIfExists(temp);
+            if (FileSystems.getDefault().supportedFileAttributeViews().contains("posix")) {
+                Files.setPosixFilePermissions(state.getParent(), PosixFilePermissions.fromString("rwx------"));
+                Files.createFile(temp, PosixFilePermissions.asFileAttribute(PosixFilePermissions.fromString("rw-------")));
+            }
+            Files.write(temp, (server.getLocalPort() + "\n" + token + "\n").getBytes(StandardCharsets.UTF_8));
+            Files.move(temp, state, StandardCopyOption.REPLACE_EXISTING, StandardCopyOption.ATOMIC_MOVE);
+            while (true) {
+                Socket client;
+                try {
+                    client = server.accept();
+                } catch (SocketTimeoutException e) {
+                    break;
+                }
+                try (Socket socket = client) {
+                    handle(socket, compiler, token);
+                } catch (IOException | RuntimeException e) {
+                    // a broken request only affects that build, barista falls back to javac for it
+                }
+            }
+        } finally {
+            // another daemon may have taken over the state file in the meantime
+            if (Files.exists(state) && new String(Files.readAllBytes(state), StandardCharsets.UTF_8).contains(token)) {
+                Files.delete(state);
+            }
+        }
+    }
+
+    private static void handle(Socket socket, JavaCompiler compiler, String token) throws IOException {
+        BufferedReader in = new BufferedReader(new InputStreamReader(socket.getInputStream(), StandardCharsets.UTF_8));
+        if (!token.equals(in.readLine())) {
+            return;
+        }
+        int count = Integer.parseInt(in.readLine());
+        String[] arguments = new String[count];
+        for (int i = 0; i < count; i++) {
+            arguments[i] = in.readLine();
+        }
+        ByteArrayOutputStream output = new ByteArrayOutputStream();
+        int code = compiler.run(null, output, output, arguments);
+        OutputStream out = socket.getOutputStream();
+        out.write(("exit " + code + "\n").getBytes(StandardCharsets.UTF_8));
+        output.writeTo(out);
+        out.flush();
+    }
+}
@@ -1,38 +1,139 @@
 use std::{
-    path::PathBuf,
+    env,
+    ffi::OsString,
+    path::Path,
     process::{Command, Stdio},
 };
 
-use javaup::config;
-
-use crate::{config::Config, roast::roast};
-
-pub fn brew(bin: Option<String>) {
-    roast(bin.clone());
-    let java_config = config::config_file();
-    let mut java_bin = config::jdkdir();
-
-    java_bin.push(java_config.default_jdk.clone().unwrap().distribution);
-    java_bin.push(java_config.default_jdk.unwrap().version);
-    java_bin.push("bin");
-    #[cfg(target_os = "windows")]
-    java_bin.push("java.exe");
-    #[cfg(not(target_os = "windows"))]
-    java_bin.push("java");
-    let binding = crate::config::get_root_path().unwrap();
-    let root = binding.display();
-    let config = Config::find_and_open_config().unwrap();
-    let mut binding = bin
-        .map(|bin| config.find_bin(bin).unwrap())
-        .unwrap_or(PathBuf::from("Main.java"));
-    binding.set_extension("");
-    let bin_path = binding.display();
-    let mut binding = Command::new(java_bin);
-    let binding = binding
-        .arg("-cp")
-        .arg(format!("{root}/lib/*.:{root}/bin"))
-        .arg(bin_path.to_string())
+use crate::{
+    config::{BinNotFoundError, Config, OpenConfigError, RunConfig},
+    diagnostics::MessageFormat,
+    module::module_name,
+    roast::{roast, RoastOptions},
+    toolchain::{self, ToolchainError},
+};
+
+#[derive(Debug, thiserror::Error)]
+pub enum BrewError {
+    #[error("{0}")]
+    Config(OpenConfigError),
+    #[error("{0}")]
+    Toolchain(ToolchainError),
+    #[error("{0}")]
+    Bin(BinNotFoundError),
+}
+
+/// The classpath a roasted brew runs with, its classes and resources in bin and its blends in lib
+pub fn run_classpath(root: &Path, config: &Config) -> OsString {
+    env::join_paths([root.join("bin")].into_iter().chain(config.lib_jars()))
+        .expect("brew path can't be used in a classpath")
+}
+
+/// Starting the jvm with a JDWP agent, so IntelliJ, VS Code or jdb can attach to it
+#[derive(clap::Args, Clone, Copy, Debug, Default)]
+pub struct DebugOptions {
+    /// listen for a debugger on the given port (5005 if no port is given)
+    #[clap(long, value_name = "port", num_args = 0..=1, default_missing_value = "5005")]
+    debug: Option<u16>,
+    /// wait for the debugger to attach before running anything
+    #[clap(long, requires = "debug")]
+    suspend: bool,
+}
+
+impl DebugOptions {
+    pub fn enabled(&self) -> bool {
+        self.debug.is_some()
+    }
+
+    /// The -agentlib:jdwp argument for the jvm, printing where to attach the debugger
+    pub fn jvm_arg(&self) -> Option<String> {
+        let port = self.debug?;
+        let address = format!("localhost:{port}");
+        if self.suspend {
+            println!("waiting for a debugger to attach on {address}");
+        } else {
+            println!("debugger can attach on {address}");
+        }
+        Some(format!(
+            "-agentlib:jdwp=transport=dt_socket,server=y,suspend={},address={address}",
+            if self.suspend { "y" } else { "n" }
+        ))
+    }
+}
+
+/// Runs the brew and returns the exit code of the program
+pub fn brew(
+    bin: Option<String>,
+    args: Vec<String>,
+    debug: DebugOptions,
+    message_format: MessageFormat,
+) -> i32 {
+    if let Err(e) = roast(
+        bin.clone(),
+        RoastOptions {
+            debug: debug.enabled(),
+            message_format,
+            ..Default::default()
+        },
+    ) {
+        eprintln!("Error roasting Brew\n{e}");
+        return 1;
+    }
+    let mut java = match java_command(bin, args, debug) {
+        Ok(java) => java,
+        Err(e) => {
+            eprintln!("Error brewing Brew\n{e}");
+            return 1;
+        }
+    };
+    let status = java.status().unwrap();
+    // a program killed by a signal has no exit code
+    status.code().unwrap_or(1)
+}
+
+/// The java command that runs an already roasted brew
+pub fn java_command(
+    bin: Option<String>,
+    args: Vec<String>,
+    debug: DebugOptions,
+) -> Result<Command, BrewError> {
+    let root = crate::config::get_root_path().unwrap();
+    let config = Config::find_and_open_config().map_err(BrewError::Config)?;
+    let jdk = toolchain::find(&config).map_err(BrewError::Toolchain)?;
+    let main_class = config
+        .main_class(bin, &config.source_root(&root))
+        .map_err(BrewError::Bin)?;
+    let default_run = RunConfig::default();
+    let run = config.run().unwrap_or(&default_run);
+    let mut command = Command::new(jdk.java());
+    command.args(run.jvm_args()).envs(run.env());
+    if let Some(agent) = debug.jvm_arg() {
+        command.arg(agent);
+    }
+    if let Some(working_dir) = run.working_dir() {
+        command.current_dir(root.join(working_dir));
+    }
+    if let Some(module) = module_name(&config.source_root(&root)) {
+        let mut paths = config.module_paths();
+        // the brew itself is an exploded module in bin
+        paths.module_path.insert(0, root.join("bin"));
+        command
+            .arg("--module-path")
+            .arg(paths.module_path())
+            .arg("-cp")
+            .arg(paths.class_path())
+            .arg("--module")
+            .arg(format!("{module}/{main_class}"));
+    } else {
+        command
+            .arg("-cp")
+            .arg(run_classpath(&root, &config))
+            .arg(main_class);
+    }
+    command
+        .args(args)
+        .stdin(Stdio::inherit())
         .stdout(Stdio::inherit())
         .stderr(Stdio::inherit());
-    binding.output().unwrap();
+    Ok(command)
 }
@@ -1,9 +1,18 @@
-use std::{collections::HashMap, path::PathBuf};
+use std::{
+    collections::{BTreeSet, HashMap},
+    fs, io,
+    path::{Path, PathBuf},
+};
 
 use semver::{Version, VersionReq};
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
 
-use crate::utils::{find_file, open_toml, FindFileError, TomlOpenError};
+use crate::{
+    fetch::MavenDependencyScopeType,
+    import::ImportedProject,
+    utils::{declared_package, find_file, has_main, open_toml, FindFileError, TomlOpenError},
+    workspace::{Workspace, WorkspaceError},
+};
 
 #[derive(Deserialize, Serialize, Debug)]
 pub struct Config {
@@ -11,18 +20,310 @@ pub struct Config {
     #[serde(default)]
     blends: HashMap<String, BlendConfig>,
     bin: Option<Vec<Bin>>,
+    lib: Option<Lib>,
+    resources: Option<ResourcesConfig>,
+    shade: Option<ShadeConfig>,
+    jar: Option<JarConfig>,
+    publish: Option<PublishConfig>,
+    package: Option<PackageConfig>,
+    run: Option<RunConfig>,
+    roast: Option<RoastConfig>,
+    toolchain: Option<ToolchainConfig>,
+    features: Option<Features>,
+    /// maven repositories searched for blends before maven central
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    repositories: Vec<String>,
+    /// the features the brew is built with, its default features unless others are selected
+    #[serde(skip)]
+    enabled_features: BTreeSet<String>,
 }
 
+/// Optional parts of a brew, `default` lists the features used unless told otherwise
+#[derive(Deserialize, Serialize, Debug, Default)]
+pub struct Features {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    default: Vec<String>,
+    #[serde(flatten)]
+    features: HashMap<String, Feature>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Default)]
+pub struct Feature {
+    /// the optional blends the feature turns on
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    blends: Vec<String>,
+    /// extra source directories relative to the brew, ie src-jackson
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    sources: Vec<String>,
+    /// other features the feature turns on
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    features: Vec<String>,
+}
+
+/// Which features to build a brew with
+#[derive(clap::Args, Clone, Debug, Default)]
+pub struct FeatureOptions {
+    /// features to turn on (comma separated)
+    #[clap(long, value_delimiter = ',')]
+    pub features: Vec<String>,
+    /// don't turn on the default features
+    #[clap(long)]
+    pub no_default_features: bool,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum FeatureError {
+    #[error("brew {0} has no feature {1}, features: {2}")]
+    Unknown(String, String, String),
+    #[error("feature {1} of brew {0} turns on blend {2}, which isn't an optional blend")]
+    NotOptional(String, String, String),
+}
+
+/// The jdk a brew is built and run with, instead of javaup's default
 #[derive(Deserialize, Serialize, Debug)]
+pub struct ToolchainConfig {
+    /// the major java version, ie 21
+    #[serde(deserialize_with = "number_or_string")]
+    version: String,
+    /// the distribution to install it from, temurin if not given
+    distribution: Option<String>,
+}
+
+impl ToolchainConfig {
+    pub fn version(&self) -> &str {
+        &self.version
+    }
+
+    pub fn distribution(&self) -> &str {
+        self.distribution.as_deref().unwrap_or("temurin")
+    }
+}
+
+/// Lets a version be written as `version = 21` as well as `version = "21"`
+fn number_or_string<'de, D: Deserializer<'de>>(deserializer: D) -> Result<String, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum NumberOrString {
+        Number(u64),
+        String(String),
+    }
+    Ok(match NumberOrString::deserialize(deserializer)? {
+        NumberOrString::Number(number) => number.to_string(),
+        NumberOrString::String(string) => string,
+    })
+}
+
+#[derive(Deserialize, Serialize, Debug, Default)]
+pub struct RoastConfig {
+    /// compile with a background jvm that stays warm between roasts instead of starting javac every time
+    #[serde(default)]
+    daemon: bool,
+}
+
+impl RoastConfig {
+    pub fn daemon(&self) -> bool {
+        self.daemon
+    }
+}
+
+/// How `barista brew` starts the jvm
+#[derive(Deserialize, Serialize, Debug, Default)]
+pub struct RunConfig {
+    /// options passed to the jvm before the main class, ie -Xmx512m, -ea or -Dkey=value
+    #[serde(default)]
+    jvm_args: Vec<String>,
+    /// environment variables set for the program
+    #[serde(default)]
+    env: HashMap<String, String>,
+    /// the directory the program runs in relative to the brew, the current directory if not given
+    working_dir: Option<String>,
+}
+
+impl RunConfig {
+    pub fn jvm_args(&self) -> &[String] {
+        &self.jvm_args
+    }
+
+    pub fn env(&self) -> &HashMap<String, String> {
+        &self.env
+    }
+
+    pub fn working_dir(&self) -> Option<&String> {
+        self.working_dir.as_ref()
+    }
+}
+
+/// Options for the native packages `barista package` builds with jpackage
+#[derive(Deserialize, Serialize, Debug, Default)]
+pub struct PackageConfig {
+    /// the icon of the application (.png on linux, .ico on windows, .icns on macos)
+    icon: Option<String>,
+    /// who distributes the application, the brew's group if not given
+    vendor: Option<String>,
+    copyright: Option<String>,
+    /// the package types to build (app-image, deb, rpm, msi, dmg ...), by default an app-image
+    /// and whichever of deb and rpm this machine can build
+    types: Option<Vec<String>>,
+    /// options the launcher always passes to the jvm, ie -Xmx512m
+    #[serde(default)]
+    java_options: Vec<String>,
+    /// the category of the application in the linux menus, ie Development
+    linux_category: Option<String>,
+    /// add the application to the desktop menus when it is installed
+    #[serde(default)]
+    shortcut: bool,
+}
+
+impl PackageConfig {
+    pub fn icon(&self) -> Option<&String> {
+        self.icon.as_ref()
+    }
+
+    pub fn vendor(&self) -> Option<&String> {
+        self.vendor.as_ref()
+    }
+
+    pub fn copyright(&self) -> Option<&String> {
+        self.copyright.as_ref()
+    }
+
+    pub fn types(&self) -> Option<&Vec<String>> {
+        self.types.as_ref()
+    }
+
+    pub fn java_options(&self) -> &[String] {
+        &self.java_options
+    }
+
+    pub fn linux_category(&self) -> Option<&String> {
+        self.linux_category.as_ref()
+    }
+
+    pub fn shortcut(&self) -> bool {
+        self.shortcut
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Default)]
+pub struct PublishConfig {
+    /// an http(s) maven repository or a file:// directory such as file://~/.m2/repository
+    repository: Option<String>,
+}
+
+impl PublishConfig {
+    pub fn repository(&self) -> Option<&String> {
+        self.repository.as_ref()
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Default)]
+pub struct JarConfig {
+    /// seconds since the unix epoch stamped on every jar entry, overridden by `SOURCE_DATE_EPOCH`
+    timestamp: Option<i64>,
+}
+
+impl JarConfig {
+    pub fn timestamp(&self) -> Option<i64> {
+        self.timestamp
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Default)]
+pub struct ResourcesConfig {
+    /// the resource directory relative to the brew, src/main/resources if not given
+    path: Option<String>,
+    /// replace ${brew.version} style placeholders when copying resources
+    #[serde(default)]
+    filter: bool,
+}
+
+impl ResourcesConfig {
+    pub fn path(&self) -> &str {
+        self.path.as_deref().unwrap_or("src/main/resources")
+    }
+
+    pub fn filter(&self) -> bool {
+        self.filter
+    }
+}
+
+/// Package relocations applied when building a fat jar
+#[derive(Deserialize, Serialize, Debug, Default)]
+pub struct ShadeConfig {
+    /// package -> the package it gets moved to, ie "com.google.gson" = "example.shaded.gson"
+    #[serde(default)]
+    relocations: HashMap<String, String>,
+}
+
+impl ShadeConfig {
+    pub fn relocations(&self) -> &HashMap<String, String> {
+        &self.relocations
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
 pub struct Bin {
     name: String,
+    /// the source of the bin relative to src, bin/{name}.java if not given
     path: Option<String>,
+    /// the fully qualified class to run, read from the package the source declares if not given
+    main_class: Option<String>,
+}
+
+impl Bin {
+    pub fn new(name: String, path: String) -> Self {
+        Self {
+            name,
+            path: Some(path),
+            main_class: None,
+        }
+    }
+
+    /// The source of the bin relative to src
+    pub fn source(&self) -> PathBuf {
+        self.path.as_ref().map_or_else(
+            || PathBuf::from_iter(["bin", &(self.name.clone() + ".java")]),
+            PathBuf::from,
+        )
+    }
+}
+
+/// The library target, what other brews get when they use this brew as a blend
+#[derive(Deserialize, Serialize, Debug, Default)]
+pub struct Lib {
+    /// the source root relative to the brew, the brew's source root if not given
+    path: Option<String>,
+    /// the packages that make up the public api of the library
+    exports: Option<Vec<String>>,
+}
+
+impl Lib {
+    pub fn path(&self) -> Option<&String> {
+        self.path.as_ref()
+    }
+
+    pub fn exports(&self) -> Option<&Vec<String>> {
+        self.exports.as_ref()
+    }
 }
 
 #[derive(Deserialize, Serialize, Debug)]
 pub struct BrewConfig {
     name: String,
     version: Version,
+    /// the maven groupId, ie com.example
+    group: Option<String>,
+    description: Option<String>,
+    /// an SPDX license identifier
+    license: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    authors: Vec<String>,
+    /// where the source lives, ie a github url
+    repository: Option<String>,
+    /// turn every file in src/bin with a main method into a bin target, true if not given
+    autobins: Option<bool>,
+    /// the source root relative to the brew, src if not given (src/main/java for brews imported from maven)
+    source: Option<String>,
 }
 
 impl BrewConfig {
@@ -33,6 +334,35 @@ impl BrewConfig {
     pub fn name(&self) -> &str {
         self.name.as_ref()
     }
+
+    /// The maven groupId of this brew, brews without a group use their name like junit:junit does
+    pub fn group(&self) -> &str {
+        self.group.as_ref().unwrap_or(&self.name)
+    }
+
+    pub fn description(&self) -> Option<&String> {
+        self.description.as_ref()
+    }
+
+    pub fn license(&self) -> Option<&String> {
+        self.license.as_ref()
+    }
+
+    pub fn authors(&self) -> &[String] {
+        &self.authors
+    }
+
+    pub fn repository(&self) -> Option<&String> {
+        self.repository.as_ref()
+    }
+
+    pub fn autobins(&self) -> bool {
+        self.autobins.unwrap_or(true)
+    }
+
+    pub fn source(&self) -> &str {
+        self.source.as_deref().unwrap_or("src")
+    }
 }
 
 fn default_version() -> VersionReq {
@@ -46,6 +376,19 @@ pub struct BlendConfig {
     #[serde(default = "default_version")]
     version: VersionReq,
     url: Option<String>,
+    /// the maven scope this blend is published with, compile if not given
+    scope: Option<MavenDependencyScopeType>,
+    /// take this blend from `[workspace.blends]` instead
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    workspace: bool,
+    /// only use this blend when a feature turns it on
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    optional: bool,
+    /// the features of a path or git blend to turn on
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    features: Vec<String>,
+    /// turn on the blend's default features, true if not given
+    default_features: Option<bool>,
 }
 
 impl BlendConfig {
@@ -55,6 +398,11 @@ impl BlendConfig {
             path: None,
             version,
             url: None,
+            scope: None,
+            workspace: false,
+            optional: false,
+            features: vec![],
+            default_features: None,
         }
     }
 
@@ -64,6 +412,11 @@ impl BlendConfig {
             path: None,
             version,
             url: Some(url),
+            scope: None,
+            workspace: false,
+            optional: false,
+            features: vec![],
+            default_features: None,
         }
     }
 
@@ -73,6 +426,11 @@ impl BlendConfig {
             path: Some(path),
             version,
             url: None,
+            scope: None,
+            workspace: false,
+            optional: false,
+            features: vec![],
+            default_features: None,
         }
     }
 
@@ -87,11 +445,52 @@ impl BlendConfig {
     pub fn path(&self) -> Option<&String> {
         self.path.as_ref()
     }
+
+    pub fn git(&self) -> Option<&String> {
+        self.url.as_ref()
+    }
+
+    pub fn scope(&self) -> MavenDependencyScopeType {
+        self.scope.unwrap_or_default()
+    }
+
+    pub fn workspace(&self) -> bool {
+        self.workspace
+    }
+
+    pub fn optional(&self) -> bool {
+        self.optional
+    }
+
+    pub fn features(&self) -> &[String] {
+        &self.features
+    }
+
+    pub fn default_features(&self) -> bool {
+        self.default_features.unwrap_or(true)
+    }
+
+    pub fn with_scope(mut self, scope: MavenDependencyScopeType) -> Self {
+        self.scope = (scope != MavenDependencyScopeType::Compile).then_some(scope);
+        self
+    }
+
+    /// Makes a relative path blend relative to `dir` instead of the brew it was declared in
+    pub fn relative_to(mut self, dir: &Path) -> Self {
+        self.path = self.path.map(|path| dir.join(path).display().to_string());
+        self
+    }
 }
-#[derive(Debug)]
+#[derive(Debug, thiserror::Error)]
 pub enum OpenConfigError {
+    #[error("{0}")]
     FindFileError(FindFileError),
+    #[error("{0}")]
     TomlOpenError(TomlOpenError),
+    #[error("{0}")]
+    Workspace(WorkspaceError),
+    #[error("{0}")]
+    Features(FeatureError),
 }
 
 impl Config {
@@ -101,22 +500,103 @@ impl Config {
             brew: BrewConfig {
                 name,
                 version: Version::new(0, 1, 0),
+                group: None,
+                description: None,
+                license: None,
+                authors: vec![],
+                repository: None,
+                autobins: None,
+                source: None,
             },
             blends: HashMap::new(),
             bin: None,
+            lib: None,
+            resources: None,
+            shade: None,
+            jar: None,
+            publish: None,
+            package: None,
+            run: None,
+            roast: None,
+            toolchain: None,
+            features: None,
+            repositories: vec![],
+            enabled_features: BTreeSet::new(),
         }
     }
 
+    /// A config for a project barista didn't create, from what its maven or gradle build says about it
+    pub fn imported(project: ImportedProject) -> Self {
+        let mut config = Self::new(project.name);
+        config.brew.group = project.group;
+        config.brew.version = project.version;
+        config.brew.description = project.description;
+        config.brew.source = project.source;
+        config.toolchain = project.release.map(|version| ToolchainConfig {
+            version,
+            distribution: None,
+        });
+        config.repositories = project.repositories;
+        config.blends = project.blends.into_iter().collect();
+        config.bin = (!project.bins.is_empty()).then_some(project.bins);
+        config
+    }
+
     pub fn open_config(path: impl Into<PathBuf>) -> Result<Self, TomlOpenError> {
         open_toml(&path.into())
     }
 
     pub fn find_and_open_config() -> Result<Self, OpenConfigError> {
+        Self::find_and_open_config_with(&[], true)
+    }
+
+    /// Finds and opens the brew's Brew.toml with the given features, instead of its default ones
+    /// when `default_features` is false
+    pub fn find_and_open_config_with(
+        features: &[String],
+        default_features: bool,
+    ) -> Result<Self, OpenConfigError> {
         Self::find_config()
             .map_err(OpenConfigError::FindFileError) // map to FindFileError
             .and_then(|config_path| {
-                Config::open_config(&config_path).map_err(OpenConfigError::TomlOpenError)
-            }) // map to TomlOpenError
+                Config::open_in_workspace_with(&config_path, features, default_features)
+            })
+    }
+
+    /// Opens a Brew.toml with its default features, replacing the blends it declares with
+    /// `workspace = true` by the workspace's
+    pub fn open_in_workspace(path: &Path) -> Result<Self, OpenConfigError> {
+        Self::open_in_workspace_with(path, &[], true)
+    }
+
+    /// Opens a Brew.toml like [`Config::open_in_workspace`], with the given features
+    pub fn open_in_workspace_with(
+        path: &Path,
+        features: &[String],
+        default_features: bool,
+    ) -> Result<Self, OpenConfigError> {
+        let mut config = Self::open_config(path).map_err(OpenConfigError::TomlOpenError)?;
+        config
+            .select_features(features, default_features)
+            .map_err(OpenConfigError::Features)?;
+        if !config.blends.values().any(BlendConfig::workspace) {
+            return Ok(config);
+        }
+        let workspace = path.parent().and_then(Workspace::containing);
+        for (name, blend) in &mut config.blends {
+            if blend.workspace {
+                let inherited = workspace
+                    .as_ref()
+                    .ok_or_else(|| WorkspaceError::NoWorkspace(name.clone()))
+                    .and_then(|workspace| {
+                        workspace
+                            .blend(name)
+                            .ok_or_else(|| WorkspaceError::NoWorkspaceBlend(name.clone()))
+                    });
+                *blend = inherited.map_err(OpenConfigError::Workspace)?;
+            }
+        }
+        Ok(config)
     }
 
     pub fn add_blend(&mut self, name: String, blend: BlendConfig) {
@@ -134,32 +614,265 @@ impl Config {
     pub fn brew(&self) -> &BrewConfig {
         &self.brew
     }
-    pub fn find_bin(&self, bin_name: String) -> Result<PathBuf, BinNotFoundError> {
-        self.bin
-            .as_ref()
-            .ok_or(BinNotFoundError::NoTargetsSpecefied)?
+
+    pub fn lib(&self) -> Option<&Lib> {
+        self.lib.as_ref()
+    }
+
+    /// Where the brew's sources are, `src` unless `[brew] source` says otherwise
+    pub fn source_root(&self, root: &Path) -> PathBuf {
+        root.join(self.brew.source())
+    }
+
+    /// Where the brew's tests are, `test` in the source root or `src/test/java` next to maven's `src/main/java`
+    pub fn test_root(&self, root: &Path) -> PathBuf {
+        match self.brew.source().strip_suffix("main/java") {
+            Some(prefix) => root.join(prefix).join("test").join("java"),
+            None => self.source_root(root).join("test"),
+        }
+    }
+
+    pub fn repositories(&self) -> &[String] {
+        &self.repositories
+    }
+
+    pub fn resources(&self) -> Option<&ResourcesConfig> {
+        self.resources.as_ref()
+    }
+
+    pub fn shade(&self) -> Option<&ShadeConfig> {
+        self.shade.as_ref()
+    }
+
+    pub fn jar_config(&self) -> Option<&JarConfig> {
+        self.jar.as_ref()
+    }
+
+    pub fn publish(&self) -> Option<&PublishConfig> {
+        self.publish.as_ref()
+    }
+
+    pub fn package(&self) -> Option<&PackageConfig> {
+        self.package.as_ref()
+    }
+
+    pub fn run(&self) -> Option<&RunConfig> {
+        self.run.as_ref()
+    }
+
+    pub fn roast_config(&self) -> Option<&RoastConfig> {
+        self.roast.as_ref()
+    }
+
+    pub fn toolchain(&self) -> Option<&ToolchainConfig> {
+        self.toolchain.as_ref()
+    }
+
+    /// Turns on the given features (and the default ones unless `default_features` is false),
+    /// along with every feature they turn on
+    pub fn select_features(
+        &mut self,
+        features: &[String],
+        default_features: bool,
+    ) -> Result<(), FeatureError> {
+        let no_features = Features::default();
+        let declared = self.features.as_ref().unwrap_or(&no_features);
+        let mut pending = features.to_vec();
+        if default_features {
+            pending.extend(declared.default.iter().cloned());
+        }
+        let mut enabled = BTreeSet::new();
+        while let Some(name) = pending.pop() {
+            if enabled.contains(&name) {
+                continue;
+            }
+            let feature = declared.features.get(&name).ok_or_else(|| {
+                let mut available = declared.features.keys().cloned().collect::<Vec<_>>();
+                available.sort();
+                FeatureError::Unknown(self.brew.name.clone(), name.clone(), available.join(", "))
+            })?;
+            if let Some(blend) = feature
+                .blends
+                .iter()
+                .find(|blend| !self.blends.get(*blend).is_some_and(BlendConfig::optional))
+            {
+                return Err(FeatureError::NotOptional(
+                    self.brew.name.clone(),
+                    name,
+                    blend.clone(),
+                ));
+            }
+            pending.extend(feature.features.iter().cloned());
+            enabled.insert(name);
+        }
+        self.enabled_features = enabled;
+        Ok(())
+    }
+
+    pub fn enabled_features(&self) -> &BTreeSet<String> {
+        &self.enabled_features
+    }
+
+    fn enabled(&self) -> impl Iterator<Item = &Feature> {
+        self.enabled_features.iter().filter_map(|name| {
+            self.features
+                .as_ref()
+                .and_then(|features| features.features.get(name))
+        })
+    }
+
+    /// The blends the brew uses with its features, sorted by name
+    pub fn active_blends(&self) -> Vec<(&String, &BlendConfig)> {
+        let mut blends = self
+            .blends
             .iter()
-            .find_map(|bin| {
-                (bin.name == bin_name).then(|| {
-                    bin.path.as_ref().map_or_else(
-                        || PathBuf::from_iter(["bin", &(bin.name.clone() + ".java")]),
-                        PathBuf::from,
-                    )
+            .filter(|(name, blend)| {
+                !blend.optional || self.enabled().any(|feature| feature.blends.contains(name))
+            })
+            .collect::<Vec<_>>();
+        blends.sort_by_key(|(name, _)| *name);
+        blends
+    }
+
+    /// The source roots of the brew, `src` (or the library's source root) and those of its features
+    pub fn source_roots(&self, root: &Path, src: &str) -> Vec<PathBuf> {
+        let mut roots = vec![root.join(src)];
+        for feature in self.enabled() {
+            roots.extend(feature.sources.iter().map(|source| root.join(source)));
+        }
+        roots.dedup();
+        roots
+    }
+
+    /// Every bin target, the ones in Brew.toml and (unless `autobins = false`) the files in src/bin with a main method,
+    /// a [[bin]] with the same name as a discovered one replaces it
+    pub fn bins(&self, source_root: &Path) -> Vec<Bin> {
+        let mut bins = self.bin.clone().unwrap_or_default();
+        if self.brew.autobins() {
+            let mut discovered = fs::read_dir(source_root.join("bin"))
+                .map(|entries| {
+                    entries
+                        .filter_map(Result::ok)
+                        .map(|entry| entry.path())
+                        .filter(|path| {
+                            path.extension()
+                                .is_some_and(|extension| extension == "java")
+                                && fs::read_to_string(path).is_ok_and(|text| has_main(&text))
+                        })
+                        .filter_map(|path| Some(path.file_stem()?.to_string_lossy().to_string()))
+                        .filter(|name| !bins.iter().any(|bin| bin.name == *name))
+                        .collect::<Vec<_>>()
                 })
+                .unwrap_or_default();
+            discovered.sort();
+            bins.extend(discovered.into_iter().map(|name| Bin {
+                name,
+                path: None,
+                main_class: None,
+            }));
+        }
+        bins
+    }
+
+    fn find_bin_target(
+        &self,
+        bin_name: String,
+        source_root: &Path,
+    ) -> Result<Bin, BinNotFoundError> {
+        let bins = self.bins(source_root);
+        if bins.is_empty() {
+            return Err(BinNotFoundError::NoTargetsSpecefied);
+        }
+        let available = bins.iter().map(|bin| bin.name.clone()).collect::<Vec<_>>();
+        bins.into_iter()
+            .find(|bin| bin.name == bin_name)
+            .ok_or_else(|| BinNotFoundError::NoTargetsMatched(bin_name, available.join(", ")))
+    }
+
+    pub fn find_bin(
+        &self,
+        bin_name: String,
+        source_root: &Path,
+    ) -> Result<PathBuf, BinNotFoundError> {
+        self.find_bin_target(bin_name, source_root)
+            .map(|bin| bin.source())
+    }
+
+    /// The source of the selected bin relative to `src`, Main.java when no bin is selected
+    pub fn bin_source(
+        &self,
+        bin: Option<String>,
+        source_root: &Path,
+    ) -> Result<PathBuf, BinNotFoundError> {
+        bin.or_else(|| self.default_bin(source_root))
+            .map_or(Ok(PathBuf::from("Main.java")), |bin| {
+                self.find_bin(bin, source_root)
             })
-            .ok_or(BinNotFoundError::NoTargetsMatched(bin_name))
+    }
+
+    /// Whether the brew is built as a library rather than a bin, that is when no bin is selected
+    /// and it either has a `[lib]` or nothing to run
+    pub fn is_library(&self, bin: Option<&str>, source_root: &Path) -> bool {
+        bin.is_none()
+            && self.default_bin(source_root).is_none()
+            && (self.lib.is_some() || !source_root.join("Main.java").exists())
+    }
+
+    /// The only [[bin]] when there is no Main.java to run, as in projects brought over with `barista init`
+    fn default_bin(&self, source_root: &Path) -> Option<String> {
+        if source_root.join("Main.java").exists() {
+            return None;
+        }
+        match self.bin.as_deref() {
+            Some([bin]) => Some(bin.name.clone()),
+            _ => None,
+        }
+    }
+
+    /// The fully qualified class the selected bin runs, either its `main_class` or
+    /// the class its source file declares, ie `bin/AWK.java` with `package tools;` runs `tools.AWK`
+    pub fn main_class(
+        &self,
+        bin: Option<String>,
+        source_root: &Path,
+    ) -> Result<String, BinNotFoundError> {
+        let bin = bin.or_else(|| self.default_bin(source_root));
+        if let Some(main_class) = bin
+            .clone()
+            .map(|bin| self.find_bin_target(bin, source_root))
+            .transpose()?
+            .and_then(|bin| bin.main_class.clone())
+        {
+            return Ok(main_class);
+        }
+        let source = source_root.join(self.bin_source(bin, source_root)?);
+        let text = fs::read_to_string(&source)
+            .map_err(|error| BinNotFoundError::Read(error, source.display().to_string()))?;
+        let class = source.file_stem().unwrap().to_string_lossy();
+        Ok(match declared_package(&text) {
+            Some(package) => format!("{package}.{class}"),
+            None => class.to_string(),
+        })
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, thiserror::Error)]
 pub enum BinNotFoundError {
+    #[error("no bin targets specified in Brew.toml or found in src/bin")]
     NoTargetsSpecefied,
-    NoTargetsMatched(String),
+    #[error("no bin target named {0}, available bins: {1}")]
+    NoTargetsMatched(String, String),
+    #[error("could not read bin source {1}: {0}")]
+    Read(io::Error, String),
+    #[error("bin source {0} does not exist")]
+    NoSource(String),
 }
 
-#[derive(Debug)]
+#[derive(Debug, thiserror::Error)]
 pub enum FindRootError {
+    #[error("{0}")]
     FindFileError(FindFileError),
+    #[error("found Brew.toml without a parent directory")]
     FoundConfigWithoutRoot,
 }
 
@@ -170,3 +883,14 @@ pub fn get_root_path() -> Result<PathBuf, FindRootError> {
         .map(|path| path.to_path_buf())
         .ok_or(FindRootError::FoundConfigWithoutRoot)
 }
+
+/// Where blends are fetched to, shared by all members when the brew is in a workspace
+pub fn get_lib_path() -> Result<PathBuf, FindRootError> {
+    Ok(shared_root()?.join("lib"))
+}
+
+/// The directory with the Brew.lock and lib of the current brew, the workspace's if it is in one
+pub fn shared_root() -> Result<PathBuf, FindRootError> {
+    let root = get_root_path()?;
+    Ok(Workspace::containing(&root).map_or(root, |workspace| workspace.root().to_path_buf()))
+}
@@ -8,7 +8,8 @@ const PSVM: &str = r#"public class Main {
 
 const GITIGNORE: &str = "/lib
 /bin
-/doc";
+/doc
+/jar";
 
 // TOOD: should probably wrap around io::Result, so the user doesn't just get creation of x file/dir failed
 // but rather creation of root directory failed ...
@@ -16,6 +17,7 @@ const GITIGNORE: &str = "/lib
 use std::{
     fs::{self, File},
     io::{self, Write},
+    path::Path,
 };
 
 #[derive(Debug, thiserror::Error)]
@@ -26,6 +28,12 @@ pub enum BrewCreationError {
     FileOpen(io::Error, String, FileType),
     #[error("error creating {2:?} file : {0}, path: {1}")]
     FileWrite(io::Error, String, FileType),
+    #[error("{0} is already a brew")]
+    AlreadyBrew(String),
+    #[error("{0}")]
+    Import(ImportError),
+    #[error("{0}")]
+    Template(TemplateError),
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -40,23 +48,64 @@ pub enum DirectoryType {
     Source,
 }
 
-use crate::config::Config;
+use crate::{
+    config::Config,
+    import::{import, ImportError},
+    template::{self, Placeholders, TemplateError},
+    utils::walk_dir,
+};
 
-pub fn create_new_brew(name: &str) -> Result<(), BrewCreationError> {
+/// Creates a brew in the directory `name` from a template, see [`template::apply`]
+pub fn create_new_brew(name: &str, template: &str) -> Result<(), BrewCreationError> {
     fs::create_dir(name).map_err(|error| {
         BrewCreationError::DirCreation(error, name.to_string(), DirectoryType::Brew)
     })?;
-    init_brew(name, name)
+    let brew_name = Path::new(name).file_name().map_or_else(
+        || name.to_string(),
+        |name| name.to_string_lossy().to_string(),
+    );
+    if let Err(e) = template::apply(template, Path::new(name), &Placeholders::new(&brew_name)) {
+        // don't leave half a brew behind
+        fs::remove_dir_all(name).ok();
+        return Err(BrewCreationError::Template(e));
+    }
+    // a template doesn't have to come with its own Brew.toml or .gitignore
+    if !Path::new(name).join("Brew.toml").exists() {
+        init_config(name, &Config::new(brew_name))?;
+    }
+    init_gitignore(name)
 }
 
-pub fn init_brew(path: &str, name: &str) -> Result<(), BrewCreationError> {
-    fs::create_dir(format!("{}/src", path)).map_err(|error| {
-        BrewCreationError::DirCreation(error, name.to_string(), DirectoryType::Source)
-    })?;
+/// Makes the project in `path` a brew, taking what it can from a pom.xml or build.gradle
+/// and only adding the files it doesn't have yet
+pub fn init_brew(path: &str) -> Result<(), BrewCreationError> {
+    if Path::new(path).join("Brew.toml").exists() {
+        return Err(BrewCreationError::AlreadyBrew(path.to_string()));
+    }
+    let name = fs::canonicalize(path)
+        .ok()
+        .and_then(|root| Some(root.file_name()?.to_string_lossy().to_string()))
+        .unwrap_or_else(|| path.to_string());
+    let config =
+        Config::imported(import(Path::new(path), name).map_err(BrewCreationError::Import)?);
 
-    init_main(path)?;
+    let source_root = config.source_root(Path::new(path));
+    fs::create_dir_all(&source_root).map_err(|error| {
+        BrewCreationError::DirCreation(
+            error,
+            source_root.display().to_string(),
+            DirectoryType::Source,
+        )
+    })?;
+    // an existing project already has its code, hello world is only for empty ones
+    if !walk_dir(&source_root)
+        .iter()
+        .any(|file| file.extension().is_some_and(|ext| ext == "java"))
+    {
+        init_main(&source_root.display().to_string())?;
+    }
     init_gitignore(path)?;
-    init_config(path, name)
+    init_config(path, &config)
 }
 
 fn init_file(path: &str, contents: &str, ft: FileType) -> Result<(), BrewCreationError> {
@@ -64,6 +113,7 @@ fn init_file(path: &str, contents: &str, ft: FileType) -> Result<(), BrewCreatio
         File::options()
             .write(true)
             .create(true)
+            .truncate(true)
             .open(path)
             .map_err(|error| BrewCreationError::FileOpen(error, path.to_string(), ft))?,
         "{}",
@@ -71,25 +121,35 @@ fn init_file(path: &str, contents: &str, ft: FileType) -> Result<(), BrewCreatio
     )
     .map_err(|error| BrewCreationError::FileWrite(error, path.to_string(), ft))
 }
-fn init_config(path: &str, name: &str) -> Result<(), BrewCreationError> {
+fn init_config(path: &str, config: &Config) -> Result<(), BrewCreationError> {
     init_file(
         &format!("{path}/Brew.toml"),
         // TODO: could be slightly better error message
-        &toml::to_string(&Config::new(name.to_string()))
+        &toml::to_string(config)
             .expect("default Brew.toml should not fail toml format [please file a bug]"),
         FileType::Config,
     )
 }
 
-// TODO: when initializing non-barista packages we should account for Main.java being already created
-fn init_main(path: &str) -> Result<(), BrewCreationError> {
-    init_file(&format!("{path}/src/Main.java"), PSVM, FileType::Main)
+fn init_main(source_root: &str) -> Result<(), BrewCreationError> {
+    init_file(&format!("{source_root}/Main.java"), PSVM, FileType::Main)
 }
 
+/// Adds the directories barista builds into to the .gitignore, keeping whatever it already ignores
 fn init_gitignore(path: &str) -> Result<(), BrewCreationError> {
-    init_file(
-        &format!("{path}/.gitignore"),
-        GITIGNORE,
-        FileType::Gitignore,
-    )
+    let path = format!("{path}/.gitignore");
+    let existing = fs::read_to_string(&path).unwrap_or_default();
+    let missing = GITIGNORE
+        .lines()
+        .filter(|line| !existing.lines().any(|existing| existing.trim() == *line))
+        .collect::<Vec<_>>();
+    if missing.is_empty() {
+        return Ok(());
+    }
+    let mut contents = existing;
+    if !contents.is_empty() && !contents.ends_with('\n') {
+        contents.push('\n');
+    }
+    contents += &missing.join("\n");
+    init_file(&path, &contents, FileType::Gitignore)
 }
@@ -0,0 +1,182 @@
+//! daemon.rs talks to barista's compile daemon (java/BaristaCompileDaemon.java), a jvm per jdk and brew
+//! that keeps javac warm between roasts
+use std::{
+    ffi::{OsStr, OsString},
+    fs,
+    io::{self, Read, Write},
+    net::{Ipv4Addr, TcpStream},
+    path::Path,
+    process::{Command, Stdio},
+    thread,
+    time::{Duration, Instant},
+};
+
+use javaup::toolchain::Toolchain;
+use sha2::{Digest, Sha256};
+
+const DAEMON_SOURCE: &str = include_str!("../java/BaristaCompileDaemon.java");
+const DAEMON_CLASS: &str = "BaristaCompileDaemon";
+/// How long a freshly started daemon gets to write its port
+const STARTUP_TIMEOUT: Duration = Duration::from_secs(10);
+/// How long a build may take before the daemon is taken to be stuck and javac is used instead
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5 * 60);
+
+#[derive(Debug, thiserror::Error)]
+pub enum DaemonError {
+    #[error("no home directory to keep the daemon in")]
+    NoHome,
+    #[error("error setting up the daemon in {1}: {0}")]
+    Setup(io::Error, String),
+    #[error("the daemon failed to compile")]
+    Build,
+    #[error("error starting the daemon: {0}")]
+    Spawn(io::Error),
+    #[error("the daemon did not start within {} seconds", STARTUP_TIMEOUT.as_secs())]
+    Timeout,
+    #[error("error talking to the daemon: {0}")]
+    Connection(io::Error),
+    #[error("the daemon did not answer within {} seconds", REQUEST_TIMEOUT.as_secs())]
+    RequestTimeout,
+    #[error("javac argument {0:?} can't be sent to the daemon")]
+    Argument(OsString),
+    #[error("the daemon sent an invalid response")]
+    Response,
+}
+
+/// Compiles with the daemon for this jdk and brew, starting it if it isn't running yet,
+/// returning javac's exit code and everything it printed
+pub fn compile(
+    jdk: &Toolchain,
+    root: &Path,
+    args: &[OsString],
+) -> Result<(i32, String), DaemonError> {
+    let dir = dirs::home_dir()
+        .ok_or(DaemonError::NoHome)?
+        .join(".barista")
+        .join("daemon");
+    let state = dir.join(format!(
+        "{}.port",
+        key(&[jdk.java_home().as_os_str(), root.as_os_str()])
+    ));
+    // the state file outlives daemons that crashed or timed out, so a failed request just means starting a new one,
+    // unless the daemon is alive but stuck, then this build is left to javac
+    match request(&state, args) {
+        Ok(response) => return Ok(response),
+        Err(DaemonError::RequestTimeout) => return Err(DaemonError::RequestTimeout),
+        Err(_) => {}
+    }
+    start(jdk, &dir, &state)?;
+    request(&state, args)
+}
+
+fn start(jdk: &Toolchain, dir: &Path, state: &Path) -> Result<(), DaemonError> {
+    // the daemon is compiled once per jdk (and version of its source)
+    let classes = dir.join(key(&[jdk.java_home().as_os_str(), DAEMON_SOURCE.as_ref()]));
+    if !classes.join(format!("{DAEMON_CLASS}.class")).exists() {
+        let source = classes.join(format!("{DAEMON_CLASS}.java"));
+        create_private_dir(dir)
+            .and_then(|()| create_private_dir(&classes))
+            .and_then(|()| fs::write(&source, DAEMON_SOURCE))
+            .map_err(|error| DaemonError::Setup(error, classes.display().to_string()))?;
+        let status = Command::new(jdk.javac())
+            .arg("-d")
+            .arg(&classes)
+            .arg(&source)
+            .status()
+            .map_err(DaemonError::Spawn)?;
+        if !status.success() {
+            return Err(DaemonError::Build);
+        }
+    }
+
+    // the state file has the token that lets anyone who can read it compile as this user
+    create_private_dir(dir)
+        .map_err(|error| DaemonError::Setup(error, dir.display().to_string()))?;
+    fs::remove_file(state).ok();
+    let mut java = Command::new(jdk.java());
+    java.arg("-cp")
+        .arg(&classes)
+        .arg(DAEMON_CLASS)
+        .arg(state)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null());
+    // ctrl-c during a build shouldn't take the daemon down with it
+    #[cfg(unix)]
+    std::os::unix::process::CommandExt::process_group(&mut java, 0);
+    java.spawn().map_err(DaemonError::Spawn)?;
+    let started = Instant::now();
+    while !state.exists() {
+        if started.elapsed() > STARTUP_TIMEOUT {
+            return Err(DaemonError::Timeout);
+        }
+        thread::sleep(Duration::from_millis(50));
+    }
+    Ok(())
+}
+
+fn request(state: &Path, args: &[OsString]) -> Result<(i32, String), DaemonError> {
+    let contents = fs::read_to_string(state).map_err(DaemonError::Connection)?;
+    let mut lines = contents.lines();
+    let port = lines
+        .next()
+        .and_then(|port| port.parse::<u16>().ok())
+        .ok_or(DaemonError::Response)?;
+    let token = lines.next().ok_or(DaemonError::Response)?;
+
+    // one argument per line
+    let mut message = format!("{token}\n{}\n", args.len());
+    for arg in args {
+        match arg.to_str() {
+            Some(arg) if !arg.contains('\n') => {
+                message += arg;
+                message.push('\n');
+            }
+            _ => return Err(DaemonError::Argument(arg.clone())),
+        }
+    }
+    let mut stream =
+        TcpStream::connect_timeout(&(Ipv4Addr::LOCALHOST, port).into(), Duration::from_secs(1))
+            .map_err(DaemonError::Connection)?;
+    stream
+        .set_read_timeout(Some(REQUEST_TIMEOUT))
+        .map_err(DaemonError::Connection)?;
+    stream
+        .write_all(message.as_bytes())
+        .map_err(DaemonError::Connection)?;
+    let mut response = String::new();
+    stream
+        .read_to_string(&mut response)
+        .map_err(|error| match error.kind() {
+            io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut => DaemonError::RequestTimeout,
+            _ => DaemonError::Connection(error),
+        })?;
+    let (status, output) = response.split_once('\n').ok_or(DaemonError::Response)?;
+    let code = status
+        .strip_prefix("exit ")
+        .and_then(|code| code.parse().ok())
+        .ok_or(DaemonError::Response)?;
+    Ok((code, output.to_string()))
+}
+
+/// Creates the directory (and its parents) so that only the user can get into it
+fn create_private_dir(dir: &Path) -> io::Result<()> {
+    let mut builder = fs::DirBuilder::new();
+    builder.recursive(true);
+    #[cfg(unix)]
+    std::os::unix::fs::DirBuilderExt::mode(&mut builder, 0o700);
+    builder.create(dir)?;
+    // a directory made by an older barista is still open to everyone
+    #[cfg(unix)]
+    fs::set_permissions(dir, std::os::unix::fs::PermissionsExt::from_mode(0o700))?;
+    Ok(())
+}
+
+fn key(parts: &[&OsStr]) -> String {
+    let mut hasher = Sha256::new();
+    for part in parts {
+        hasher.update(part.to_string_lossy().as_bytes());
+        hasher.update([0]);
+    }
+    format!("{:x}", hasher.finalize())[..16].to_string()
+}
@@ -0,0 +1,351 @@
+//! diagnostics.rs turns what javac and javadoc print into diagnostics, so they can be shown the same way
+//! for every tool, or as json for editors and CI annotators
+use std::{
+    fmt::{self, Display},
+    path::{Path, PathBuf},
+    process::Output,
+};
+
+use serde::Serialize;
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum MessageFormat {
+    /// readable diagnostics on stderr
+    #[default]
+    Human,
+    /// one json object per diagnostic on stdout
+    Json,
+}
+
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Error,
+    Warning,
+    Note,
+}
+
+impl Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Error => "error",
+            Self::Warning => "warning",
+            Self::Note => "note",
+        })
+    }
+}
+
+#[derive(Serialize, Debug)]
+pub struct Diagnostic {
+    /// javac or javadoc
+    pub tool: &'static str,
+    pub file: Option<PathBuf>,
+    pub line: Option<u32>,
+    pub column: Option<u32>,
+    pub severity: Severity,
+    /// the lint a warning comes from, ie deprecation for `warning: [deprecation] ...`
+    pub code: Option<String>,
+    /// the message, with the details javac gives on the following lines (symbol: ..., location: ...)
+    pub message: String,
+    /// the line of source the tool quoted
+    #[serde(skip)]
+    source: Option<String>,
+}
+
+impl Diagnostic {
+    /// Renders the diagnostic with its file relative to the brew root
+    pub fn render(&self, root: &Path) -> String {
+        let mut lines = self.message.lines();
+        let mut out = format!("{}", self.severity);
+        if let Some(code) = &self.code {
+            out += &format!("[{code}]");
+        }
+        out += &format!(": {}", lines.next().unwrap_or_default());
+        if let (Some(file), Some(line)) = (&self.file, self.line) {
+            let file = file.strip_prefix(root).unwrap_or(file);
+            out += &format!("\n  --> {}:{line}", file.display());
+            if let Some(column) = self.column {
+                out += &format!(":{column}");
+            }
+            let number = line.to_string();
+            let gutter = " ".repeat(number.len());
+            if let Some(source) = &self.source {
+                out += &format!("\n{gutter} |\n{number} | {source}");
+                if let Some(column) = self.column {
+                    out += &format!("\n{gutter} | {}^", " ".repeat(column as usize - 1));
+                }
+            }
+            for detail in lines {
+                out += &format!("\n{gutter} = {}", detail.trim());
+            }
+        } else {
+            for detail in lines {
+                out += &format!("\n  = {}", detail.trim());
+            }
+        }
+        out
+    }
+}
+
+/// Splits a tool's output into its diagnostics and the lines that aren't part of any
+pub fn parse(tool: &'static str, output: &str) -> (Vec<Diagnostic>, Vec<String>) {
+    let mut diagnostics: Vec<Diagnostic> = vec![];
+    let mut other = vec![];
+    for line in output.lines() {
+        if let Some(diagnostic) = header(tool, line) {
+            diagnostics.push(diagnostic);
+        } else if is_summary(line) {
+            continue;
+        } else if let Some(diagnostic) = diagnostics.last_mut() {
+            let caret = line.trim_start();
+            if diagnostic.file.is_some() && diagnostic.source.is_none() {
+                diagnostic.source = Some(line.to_string());
+            } else if diagnostic.source.is_some() && diagnostic.column.is_none() && caret == "^" {
+                diagnostic.column = Some((line.len() - caret.len() + 1) as u32);
+            } else {
+                diagnostic.message.push('\n');
+                diagnostic.message += line.trim();
+            }
+        } else if !line.trim().is_empty() {
+            other.push(line.to_string());
+        }
+    }
+    (diagnostics, other)
+}
+
+/// Prints the diagnostics of a tool's output in the chosen format, returning how many errors there were
+pub fn report(tool: &'static str, output: &str, format: MessageFormat, root: &Path) -> usize {
+    let (diagnostics, other) = parse(tool, output);
+    // whatever isn't a diagnostic (ie a crashed jvm) still has to be seen, but can't go into the json on stdout
+    for line in other {
+        eprintln!("{line}");
+    }
+    for diagnostic in &diagnostics {
+        match format {
+            MessageFormat::Human => eprintln!("{}\n", diagnostic.render(root)),
+            MessageFormat::Json => println!(
+                "{}",
+                serde_json::to_string(diagnostic).expect("diagnostics are always valid json")
+            ),
+        }
+    }
+    let count = |severity| {
+        diagnostics
+            .iter()
+            .filter(|diagnostic| diagnostic.severity == severity)
+            .count()
+    };
+    let (errors, warnings) = (count(Severity::Error), count(Severity::Warning));
+    if format == MessageFormat::Human && errors + warnings > 0 {
+        eprintln!("{tool}: {errors} error(s), {warnings} warning(s)");
+    }
+    errors
+}
+
+/// Everything a tool printed, javac and javadoc use both stdout and stderr
+pub fn tool_output(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stdout).to_string() + &String::from_utf8_lossy(&output.stderr)
+}
+
+/// `File.java:12: error: message`, `warning: message` or `Note: message`
+fn header(tool: &'static str, line: &str) -> Option<Diagnostic> {
+    let severities = [
+        ("error", Severity::Error),
+        ("warning", Severity::Warning),
+        ("Note", Severity::Note),
+        ("note", Severity::Note),
+    ];
+    let (file, line_number, severity, message) =
+        severities.iter().find_map(|(name, severity)| {
+            if let Some(message) = line.strip_prefix(&format!("{name}: ")) {
+                return Some((None, None, *severity, message));
+            }
+            let (location, message) = line.split_once(&format!(": {name}: "))?;
+            let (file, line_number) = location.rsplit_once(':')?;
+            Some((
+                Some(PathBuf::from(file)),
+                Some(line_number.parse().ok()?),
+                *severity,
+                message,
+            ))
+        })?;
+    let (code, message) = match message
+        .strip_prefix('[')
+        .and_then(|message| message.split_once("] "))
+    {
+        Some((code, message)) => (Some(code.to_string()), message),
+        None => (None, message),
+    };
+    Some(Diagnostic {
+        tool,
+        file,
+        line: line_number,
+        column: None,
+        severity,
+        code,
+        message: message.to_string(),
+        source: None,
+    })
+}
+
+/// The `2 errors` or `1 warning` javac ends with
+fn is_summary(line: &str) -> bool {
+    line.split_once(' ').is_some_and(|(count, kind)| {
+        count.parse::<usize>().is_ok()
+            && matches!(kind, "error" | "errors" | "warning" | "warnings")
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // what javac 21 prints for src/p/A.java with -Xlint:all
+    const JAVAC: &str = r#"src/p/A.java:6: error: incompatible types: String cannot be converted to int
+        int x = "s";
+                ^
+src/p/A.java:7: error: cannot find symbol
+        undefined();
+        ^
+  symbol:   method undefined()
+  location: class A
+src/p/A.java:9: warning: [unchecked] unchecked call to add(E) as a member of the raw type List
+        l.add(1);
+             ^
+  where E is a type-variable:
+    E extends Object declared in interface List
+Note: src/p/B.java uses or overrides a deprecated API.
+Note: Recompile with -Xlint:deprecation for details.
+2 errors
+1 warning
+"#;
+
+    // what javadoc 21 prints for a wrong @param
+    const JAVADOC: &str = "src/p/B.java:5: error: @param name not found
+    /** @param y nope */
+               ^
+src/p/B.java:6: warning: no @param for x
+    public void g(int x) {}
+                ^
+1 error
+1 warning
+";
+
+    #[test]
+    fn parses_javac_output() {
+        let (diagnostics, other) = parse("javac", JAVAC);
+        assert!(other.is_empty(), "{other:?}");
+        assert_eq!(diagnostics.len(), 5);
+
+        let incompatible = &diagnostics[0];
+        assert_eq!(incompatible.file, Some(PathBuf::from("src/p/A.java")));
+        assert_eq!(incompatible.line, Some(6));
+        assert_eq!(incompatible.column, Some(17));
+        assert_eq!(incompatible.severity, Severity::Error);
+        assert_eq!(incompatible.code, None);
+        assert_eq!(
+            incompatible.message,
+            "incompatible types: String cannot be converted to int"
+        );
+        assert_eq!(
+            incompatible.source.as_deref(),
+            Some(r#"        int x = "s";"#)
+        );
+
+        // the details on the following lines belong to the message
+        let symbol = &diagnostics[1];
+        assert_eq!(symbol.column, Some(9));
+        assert_eq!(
+            symbol.message,
+            "cannot find symbol\nsymbol:   method undefined()\nlocation: class A"
+        );
+
+        let unchecked = &diagnostics[2];
+        assert_eq!(unchecked.severity, Severity::Warning);
+        assert_eq!(unchecked.code.as_deref(), Some("unchecked"));
+        assert_eq!(unchecked.column, Some(14));
+        assert_eq!(
+            unchecked.message,
+            "unchecked call to add(E) as a member of the raw type List\n\
+             where E is a type-variable:\n\
+             E extends Object declared in interface List"
+        );
+
+        // notes have no location
+        let note = &diagnostics[3];
+        assert_eq!(note.severity, Severity::Note);
+        assert_eq!(note.file, None);
+        assert_eq!(note.line, None);
+        assert_eq!(
+            note.message,
+            "src/p/B.java uses or overrides a deprecated API."
+        );
+    }
+
+    #[test]
+    fn parses_javadoc_output() {
+        let (diagnostics, other) = parse("javadoc", JAVADOC);
+        assert!(other.is_empty(), "{other:?}");
+        assert_eq!(diagnostics.len(), 2);
+        assert_eq!(diagnostics[0].tool, "javadoc");
+        assert_eq!(diagnostics[0].severity, Severity::Error);
+        assert_eq!(diagnostics[0].line, Some(5));
+        assert_eq!(diagnostics[0].column, Some(16));
+        assert_eq!(diagnostics[0].message, "@param name not found");
+        assert_eq!(diagnostics[1].severity, Severity::Warning);
+        assert_eq!(diagnostics[1].message, "no @param for x");
+    }
+
+    #[test]
+    fn keeps_windows_drive_letters_in_the_file() {
+        let diagnostic = header(
+            "javac",
+            r"C:\Users\me\brew\src\Main.java:3: error: ';' expected",
+        )
+        .unwrap();
+        assert_eq!(
+            diagnostic.file,
+            Some(PathBuf::from(r"C:\Users\me\brew\src\Main.java"))
+        );
+        assert_eq!(diagnostic.line, Some(3));
+        assert_eq!(diagnostic.message, "';' expected");
+    }
+
+    #[test]
+    fn parses_headers_without_a_location() {
+        let diagnostic = header(
+            "javadoc",
+            "error: No public or protected classes found to document.",
+        )
+        .unwrap();
+        assert_eq!(diagnostic.file, None);
+        assert_eq!(diagnostic.severity, Severity::Error);
+        assert!(header("javac", "  symbol:   method undefined()").is_none());
+        // a colon in the message doesn't make it a location
+        assert!(header("javac", "location: class A").is_none());
+    }
+
+    #[test]
+    fn lines_outside_of_diagnostics_are_kept() {
+        let (diagnostics, other) = parse(
+            "javac",
+            "Exception in thread \"main\" java.lang.OutOfMemoryError\n\nsrc/A.java:1: error: oops\nclass A {\n^\n",
+        );
+        assert_eq!(
+            other,
+            ["Exception in thread \"main\" java.lang.OutOfMemoryError"]
+        );
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].column, Some(1));
+    }
+
+    #[test]
+    fn recognizes_summaries() {
+        for summary in ["1 error", "2 errors", "1 warning", "100 warnings"] {
+            assert!(is_summary(summary), "{summary}");
+        }
+        for line in ["error: oops", "2 errors found", "many errors", "1 note"] {
+            assert!(!is_summary(line), "{line}");
+        }
+    }
+}
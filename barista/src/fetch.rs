@@ -1,51 +1,116 @@
 use std::{
+    collections::{BTreeMap, BTreeSet},
     fs::{self, File},
     io::Write,
     path::{Path, PathBuf},
+    process::{Command, Stdio},
     str::FromStr,
     sync::{Arc, Mutex},
 };
 
 use crate::{
-    config::{BlendConfig, Config},
+    config::{BlendConfig, Config, FeatureError, OpenConfigError},
     lock::{LockFile, Package},
     utils::unless_exists,
+    workspace::{Workspace, WorkspaceError},
 };
 use async_recursion::async_recursion;
 use lenient_semver::Version;
 use reqwest::Client;
 use semver::{BuildMetadata, Prerelease};
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use tokio::runtime::Builder;
 
+#[derive(Debug, thiserror::Error)]
+pub enum FetchError {
+    #[error("blends use each other in a cycle: {0}")]
+    Cycle(String),
+    #[error("could not use blend {0}: {1}")]
+    Features(String, FeatureError),
+    #[error("could not open blend {0}: {1}")]
+    Blend(String, Box<OpenConfigError>),
+    #[error("{0}")]
+    Workspace(WorkspaceError),
+}
+
 impl Config {
-    pub fn fetch(&self) {
-        let binding = crate::config::get_root_path().unwrap();
-        let root = binding.display();
-        unless_exists(Path::new(&format!("{root}/lib")), || {
-            fs::create_dir_all(format!("{root}/lib"))
+    pub fn fetch(&self) -> Result<(), FetchError> {
+        let root = crate::config::get_root_path().unwrap();
+        let lib = crate::config::get_lib_path().unwrap();
+        unless_exists(&lib, || {
+            fs::create_dir_all(&lib)
                 .expect("Failed to create Brew Library directory (lib) when building")
         });
-        let lock_file = LockFile::new(self.brew().name().to_owned(), self.brew().version().clone());
+        let workspace = Workspace::find();
+        let lock_file = match &workspace {
+            Some(_) => LockFile::workspace(),
+            None => LockFile::new(self.brew().name().to_owned(), self.brew().version().clone()),
+        };
         let locked_lock_file = Arc::new(Mutex::new(lock_file));
         let runtime = Builder::new_multi_thread()
             .worker_threads(1)
             .enable_all()
             .build()
             .unwrap();
-        let mut dep_handles = Vec::with_capacity(self.blends().len());
-        for (dep_name, dep_info) in self.blends().clone() {
-            if dep_info.author().is_some() {
-                dep_handles
-                    .push(runtime.spawn(dep_info.fetch_maven(dep_name, locked_lock_file.clone())));
-            } else if dep_info.path().is_some() {
-                dep_info.fetch_path();
+
+        let mut graph = BlendGraph::new(self, &lib);
+        graph.add_brew(self, &root)?;
+        lock_features(self, &locked_lock_file);
+        // the workspace's Brew.lock has the blends of every member, but only this brew's blends are built
+        if let Some(workspace) = &workspace {
+            for (member, config) in workspace.member_configs().map_err(FetchError::Workspace)? {
+                if config.brew().name() != self.brew().name() {
+                    graph.add_brew(&config, &member)?;
+                    lock_features(&config, &locked_lock_file);
+                }
             }
         }
-        for handle in dep_handles {
+
+        let BlendGraph { maven, local, .. } = graph;
+        let repositories = self.maven_repositories();
+        let dep_handles = maven
+            .into_iter()
+            .map(|maven| {
+                let dir = maven.blend.scope().lib_dir(&lib);
+                let handle = runtime.spawn(maven.blend.fetch_maven(
+                    maven.name,
+                    repositories.clone(),
+                    dir,
+                    locked_lock_file.clone(),
+                ));
+                (maven.owners, handle)
+            })
+            .collect::<Vec<_>>();
+        for (owners, handle) in dep_handles {
             //     // The `spawn` method returns a `JoinHandle`. A `JoinHandle` is
             //     // a future, so we can wait for it using `block_on`.
-            runtime.block_on(handle).unwrap();
+            for jar in runtime.block_on(handle).unwrap() {
+                for owner in &owners {
+                    lock_jar(owner, &jar, &lib, &locked_lock_file);
+                }
+            }
+        }
+        // every maven blend has to be in lib before the local blends are compiled against them
+        for blend in build_order(local, &lib) {
+            if blend.build {
+                let dirs = blend
+                    .owners
+                    .values()
+                    .map(|scope| scope.lib_dir(&lib))
+                    .collect();
+                blend.config.jar(&blend.dir, &dirs);
+            }
+            let brew = blend.config.brew();
+            let jar = format!("{}-{}.jar", brew.name(), brew.version());
+            for (owner, scope) in &blend.owners {
+                lock_jar(
+                    owner,
+                    &scope.lib_dir(&lib).join(&jar),
+                    &lib,
+                    &locked_lock_file,
+                );
+            }
+            write_package_to_lockfile(blend.package(), locked_lock_file.clone());
         }
         let lock_file = locked_lock_file.lock().unwrap();
         let lock_file_path = get_lock_path();
@@ -56,64 +121,374 @@ impl Config {
             toml::ser::to_string(&*lock_file).unwrap()
         )
         .expect("Could not write new Barista.lock after fetching dependencies");
+        Ok(())
+    }
+
+    /// The repositories blends are looked up in, in order, the brew's own before maven central
+    fn maven_repositories(&self) -> Arc<[String]> {
+        self.repositories()
+            .iter()
+            .map(|repository| repository.trim_end_matches('/').to_string())
+            .chain([MAVEN_CENTRAL.to_string()])
+            .collect()
     }
 }
-impl BlendConfig {
-    async fn fetch_maven(self, name: String, locked_lock_file: Arc<Mutex<LockFile>>) {
-        let client = Client::new();
-        if let Some(maven_author) = self.author() {
-            let req_url = format!(
-                "https://repo1.maven.org/maven2/{}/{}/",
-                maven_author.replace('.', "/"),
-                name
-            );
-            let dep_info_url = req_url.clone() + "maven-metadata.xml";
-            let text = client
-                .get(dep_info_url)
-                .send()
-                .await
-                .unwrap()
-                .text()
-                .await
-                .unwrap();
-            let dep_info_xml = quick_xml::de::from_str::<Metadata>(&text).unwrap();
-            let version = self.find_best_version(dep_info_xml).unwrap_or_else(|| {
-                panic!(
-                    "couldn't find resolve version for {name} with version {}",
-                    self.version()
-                )
-            });
 
-            let blend_dep = Package::new(
-                name.clone(),
-                version.1.to_string(),
-                maven_author.clone(),
-                // TODO: get the user frinedly url for this dependency too
-                req_url.clone(),
-                None,
-                None,
-            );
+const MAVEN_CENTRAL: &str = "https://repo1.maven.org/maven2";
 
-            finish_download_dep(
-                name,
-                version.0,
-                req_url,
-                client,
-                locked_lock_file,
-                blend_dep,
+/// The first repository that has the artifact, with its directory url and the artifact's maven-metadata.xml
+async fn find_artifact(
+    client: &Client,
+    repositories: &[String],
+    group: &str,
+    artifact: &str,
+) -> Option<(String, String)> {
+    for repository in repositories {
+        let req_url = format!("{repository}/{}/{artifact}/", group.replace('.', "/"));
+        let Ok(response) = client
+            .get(req_url.clone() + "maven-metadata.xml")
+            .send()
+            .await
+        else {
+            continue;
+        };
+        if response.status().is_success() {
+            if let Ok(text) = response.text().await {
+                return Some((req_url, text));
+            }
+        }
+    }
+    None
+}
+
+/// A path or git blend, a brew of its own that is built into a jar in its own directory
+struct LocalBlend {
+    name: String,
+    dir: PathBuf,
+    /// where the blend came from, ie path+/home/me/core or git+https://github.com/me/core#rev
+    source: String,
+    config: Config,
+    /// whether the brew being fetched uses the blend, the blends of other workspace members are only locked
+    build: bool,
+    /// the brews (workspace members) using the blend, each with the scope it needs the blend in
+    owners: BTreeMap<String, MavenDependencyScopeType>,
+}
+
+impl LocalBlend {
+    /// The blend under its maven coordinates, with the blends and features it uses itself
+    fn package(&self) -> Package {
+        let brew = self.config.brew();
+        let dependencies = self
+            .config
+            .active_blends()
+            .into_iter()
+            .map(|(name, _)| name.clone())
+            .collect();
+        let mut package = Package::new(
+            self.name.clone(),
+            brew.version().to_string(),
+            brew.group().to_string(),
+            brew.repository()
+                .map_or_else(|| self.dir.display().to_string(), ToString::to_string),
+            Some(self.source.clone()),
+            Some(dependencies),
+        );
+        package.set_features(self.config.enabled_features().iter().cloned().collect());
+        package
+    }
+}
+
+/// The blends of a brew together with the blends of its path and git blends, all the way down,
+/// and which brews (workspace members) use each of them
+struct BlendGraph {
+    lib: PathBuf,
+    /// the brew being fetched, only the local blends it uses are built
+    fetched: String,
+    /// every maven blend once for each scope it is used in, the first brew to ask for one decides its version
+    maven: Vec<MavenBlend>,
+    local: Vec<LocalBlend>,
+}
+
+struct MavenBlend {
+    name: String,
+    blend: BlendConfig,
+    /// the brews (workspace members) using the blend, a workspace shares lib but not its blends
+    owners: BTreeSet<String>,
+}
+
+impl BlendGraph {
+    fn new(fetched: &Config, lib: &Path) -> Self {
+        Self {
+            lib: lib.to_path_buf(),
+            fetched: fetched.brew().name().to_string(),
+            maven: vec![],
+            local: vec![],
+        }
+    }
+
+    fn add_brew(&mut self, brew: &Config, root: &Path) -> Result<(), FetchError> {
+        let root = fs::canonicalize(root).unwrap_or_else(|_| root.to_path_buf());
+        let owner = brew.brew().name().to_string();
+        let mut stack = vec![(owner.clone(), root.clone())];
+        self.add_blends(active_blends(brew), &root, &owner, None, &mut stack)
+    }
+
+    /// Adds blends `owner` uses through the brew in `base`, `stack` is the chain of local blends that led here
+    /// and `parent` the scope of the local blend declaring them
+    fn add_blends(
+        &mut self,
+        blends: Vec<(String, BlendConfig)>,
+        base: &Path,
+        owner: &str,
+        parent: Option<MavenDependencyScopeType>,
+        stack: &mut Vec<(String, PathBuf)>,
+    ) -> Result<(), FetchError> {
+        for (name, blend) in blends {
+            let scope = match parent {
+                Some(parent) => match parent.inherit(blend.scope()) {
+                    Some(scope) => scope,
+                    None => continue,
+                },
+                None => blend.scope(),
+            };
+            let blend = blend.with_scope(scope);
+            if blend.author().is_some() {
+                match self.maven.iter_mut().find(|known| {
+                    known.name == name
+                        && known.blend.author() == blend.author()
+                        && known.blend.scope() == scope
+                }) {
+                    Some(known) => {
+                        known.owners.insert(owner.to_string());
+                    }
+                    None => self.maven.push(MavenBlend {
+                        name,
+                        blend,
+                        owners: BTreeSet::from([owner.to_string()]),
+                    }),
+                }
+                continue;
+            }
+            let source = match blend.git() {
+                Some(url) => checkout_git(&name, url, &self.lib),
+                None => String::new(),
+            };
+            let Some(dir) = blend_dir(&name, &blend, base, &self.lib) else {
+                continue;
+            };
+            if let Some(start) = stack.iter().position(|(_, visited)| *visited == dir) {
+                let cycle = stack[start..]
+                    .iter()
+                    .map(|(name, _)| name.as_str())
+                    .chain([name.as_str()])
+                    .collect::<Vec<_>>();
+                return Err(FetchError::Cycle(cycle.join(" -> ")));
+            }
+            // the brew being fetched is added first, so a blend it shares with another member is already built,
+            // but another brew may use more of the blend's features or need it in a wider scope
+            if let Some(known) = self.local.iter_mut().find(|known| known.dir == dir) {
+                let enabled = known.config.enabled_features().clone();
+                let features = enabled
+                    .iter()
+                    .chain(blend.features())
+                    .cloned()
+                    .collect::<Vec<_>>();
+                known
+                    .config
+                    .select_features(&features, blend.default_features())
+                    .map_err(|e| FetchError::Features(name.clone(), e))?;
+                let previous = known.owners.get(owner).copied();
+                let widest = previous.map_or(scope, |previous| previous.widest(scope));
+                known.owners.insert(owner.to_string(), widest);
+                known.build |= owner == self.fetched;
+                // the blends the new features use are used by every brew using the blend
+                let owners = if *known.config.enabled_features() != enabled {
+                    known.owners.clone()
+                } else if previous != Some(widest) {
+                    BTreeMap::from([(owner.to_string(), widest)])
+                } else {
+                    BTreeMap::new()
+                };
+                let blends = active_blends(&known.config);
+                stack.push((name.clone(), dir.clone()));
+                for (owner, scope) in owners {
+                    self.add_blends(blends.clone(), &dir, &owner, Some(scope), stack)?;
+                }
+                stack.pop();
+                continue;
+            }
+            let config = Config::open_in_workspace_with(
+                &dir.join("Brew.toml"),
+                blend.features(),
+                blend.default_features(),
             )
-            .await;
-        } else {
+            .map_err(|e| FetchError::Blend(name.clone(), Box::new(e)))?;
+            stack.push((name.clone(), dir.clone()));
+            self.add_blends(active_blends(&config), &dir, owner, Some(scope), stack)?;
+            stack.pop();
+            let source = if blend.git().is_some() {
+                source
+            } else {
+                format!("path+{}", dir.display())
+            };
+            self.local.push(LocalBlend {
+                name,
+                dir,
+                source,
+                config,
+                build: owner == self.fetched,
+                owners: BTreeMap::from([(owner.to_string(), scope)]),
+            });
         }
+        Ok(())
     }
+}
 
-    fn fetch_path(&self) {
-        if let Some(path) = self.path() {
-            let dep =
-                Config::open_config(format!("{path}{}Brew.toml", std::path::MAIN_SEPARATOR_STR))
-                    .unwrap();
-            dep.jar(&path);
+/// The local blends ordered so that every blend comes after the local blends it uses
+fn build_order(local: Vec<LocalBlend>, lib: &Path) -> Vec<LocalBlend> {
+    fn visit(index: usize, local: &[LocalBlend], lib: &Path, order: &mut Vec<usize>) {
+        if order.contains(&index) {
+            return;
         }
+        let blend = &local[index];
+        for (name, used) in blend.config.active_blends() {
+            let Some(dir) = blend_dir(name, used, &blend.dir, lib) else {
+                continue;
+            };
+            if let Some(used) = local.iter().position(|known| known.dir == dir) {
+                visit(used, local, lib, order);
+            }
+        }
+        order.push(index);
+    }
+    let mut order = vec![];
+    for index in 0..local.len() {
+        visit(index, &local, lib, &mut order);
+    }
+    let mut local = local.into_iter().map(Some).collect::<Vec<_>>();
+    order
+        .into_iter()
+        .filter_map(|index| local[index].take())
+        .collect()
+}
+
+fn active_blends(config: &Config) -> Vec<(String, BlendConfig)> {
+    config
+        .active_blends()
+        .into_iter()
+        .map(|(name, blend)| (name.clone(), blend.clone()))
+        .collect()
+}
+
+/// The directory of a path or git blend, path blends are relative to the brew declaring them in `base`
+fn blend_dir(name: &str, blend: &BlendConfig, base: &Path, lib: &Path) -> Option<PathBuf> {
+    let dir = if let Some(path) = blend.path() {
+        base.join(path)
+    } else if blend.git().is_some() {
+        lib.join("git").join(name)
+    } else {
+        return None;
+    };
+    Some(fs::canonicalize(&dir).unwrap_or(dir))
+}
+
+/// Records the features a brew is built with, if it has any
+fn lock_features(config: &Config, locked_lock_file: &Arc<Mutex<LockFile>>) {
+    if let Ok(mut lock_file) = locked_lock_file.lock() {
+        lock_file.set_features(
+            config.brew().name().to_string(),
+            config.enabled_features().iter().cloned().collect(),
+        );
+    }
+}
+
+/// Records that a brew uses the blend jar at `jar` in `lib`, the jars in lib are only a cache of every blend
+/// ever fetched so the brew's classpath and packaging go by what the lock file says it resolved to
+fn lock_jar(brew: &str, jar: &Path, lib: &Path, locked_lock_file: &Arc<Mutex<LockFile>>) {
+    let jar = jar
+        .strip_prefix(lib)
+        .unwrap_or(jar)
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/");
+    if let Ok(mut lock_file) = locked_lock_file.lock() {
+        lock_file.add_jar(brew, jar);
+    }
+}
+
+/// Clones a git blend into `lib/git/{name}` (once, later fetches reuse the checkout),
+/// returning where it came from
+fn checkout_git(name: &str, url: &str, lib: &Path) -> String {
+    let checkout = lib.join("git").join(name);
+    unless_exists(&checkout, || {
+        let status = Command::new("git")
+            .arg("clone")
+            .arg("--depth")
+            .arg("1")
+            .arg(url)
+            .arg(&checkout)
+            .stdout(Stdio::inherit())
+            .stderr(Stdio::inherit())
+            .status()
+            .expect("Failed to run git, is it installed?");
+        if !status.success() {
+            panic!("Failed to clone git blend {name} from {url}")
+        }
+    });
+    let rev = Command::new("git")
+        .arg("rev-parse")
+        .arg("HEAD")
+        .current_dir(&checkout)
+        .output()
+        .expect("Failed to run git, is it installed?");
+    let rev = String::from_utf8_lossy(&rev.stdout).trim().to_string();
+    format!("git+{url}#{rev}")
+}
+
+impl BlendConfig {
+    async fn fetch_maven(
+        self,
+        name: String,
+        repositories: Arc<[String]>,
+        lib: PathBuf,
+        locked_lock_file: Arc<Mutex<LockFile>>,
+    ) -> Vec<PathBuf> {
+        let client = Client::new();
+        let Some(maven_author) = self.author() else {
+            return vec![];
+        };
+        let (req_url, text) = find_artifact(&client, &repositories, maven_author, &name)
+            .await
+            .unwrap_or_else(|| panic!("couldn't find {maven_author}:{name} in any repository"));
+        let dep_info_xml = quick_xml::de::from_str::<Metadata>(&text).unwrap();
+        let version = self.find_best_version(dep_info_xml).unwrap_or_else(|| {
+            panic!(
+                "couldn't find resolve version for {name} with version {}",
+                self.version()
+            )
+        });
+
+        let blend_dep = Package::new(
+            name.clone(),
+            version.1.to_string(),
+            maven_author.clone(),
+            // TODO: get the user frinedly url for this dependency too
+            req_url.clone(),
+            None,
+            None,
+        );
+
+        finish_download_dep(
+            version.0,
+            req_url,
+            client,
+            repositories,
+            &lib,
+            locked_lock_file,
+            blend_dep,
+        )
+        .await
     }
 
     fn find_best_version<'a>(
@@ -125,31 +500,51 @@ impl BlendConfig {
             .versions
             .version
             .iter()
-            .filter_map(|s| (Version::parse(s).ok().map(|v| (*s, v))))
+            .filter_map(|s| Version::parse(s).ok().map(|v| (*s, v)))
             .map(|(s, v)| (s, to_version(v)))
             .filter(|(_, version)| self.version().matches(version))
             .max()
     }
 }
+/// Downloads a blend into `lib` (or the directory in it for the blend's scope), and then the blends it uses,
+/// returning the jars of all of them
 #[async_recursion]
 async fn finish_download_dep(
-    name: String,
     version: &str,
     req_url: String,
     client: Client,
+    repositories: Arc<[String]>,
+    lib: &Path,
     locked_lock_file: Arc<Mutex<LockFile>>,
     package: Package,
-) {
+) -> Vec<PathBuf> {
     let (dep_url, dep_url_info, dep_path) = {
-        let path = format!("{}-{}", name, version);
+        let path = format!("{}-{}", package.name(), version);
         let url_base = req_url + version + "/" + &path;
         (
             url_base.clone() + ".jar",
             url_base + ".pom",
-            // TODO: this doesn't account for the case where barista isn't ran from the project root dir  (where lib doesnt exist)
-            format!("lib/{path}") + ".jar",
+            lib.join(path + ".jar"),
         )
     };
+    // lib doubles as a cache, released maven artifacts never change
+    if dep_path.exists() && !version.ends_with("-SNAPSHOT") {
+        let mut jars = download_dep_dep(
+            client,
+            &dep_url_info,
+            repositories,
+            lib,
+            locked_lock_file,
+            package,
+        )
+        .await;
+        jars.push(dep_path);
+        return jars;
+    }
+    unless_exists(lib, || {
+        fs::create_dir_all(lib)
+            .unwrap_or_else(|e| panic!("Failed to create '{}': {e}", lib.display()))
+    });
     let res = client
         .get(&dep_url)
         .send()
@@ -158,11 +553,24 @@ async fn finish_download_dep(
         .unwrap();
     let dep = res.bytes().await.unwrap();
     let mut file = File::create(&dep_path)
-        .or(Err(format!("Failed to create file '{}'", dep_path)))
+        .or(Err(format!(
+            "Failed to create file '{}'",
+            dep_path.display()
+        )))
         .unwrap();
     file.write_all(&dep[..])
-        .unwrap_or_else(|_| panic!("couldn't write to file '{}'", dep_path));
-    download_dep_dep(client, &dep_url_info, locked_lock_file, package).await;
+        .unwrap_or_else(|_| panic!("couldn't write to file '{}'", dep_path.display()));
+    let mut jars = download_dep_dep(
+        client,
+        &dep_url_info,
+        repositories,
+        lib,
+        locked_lock_file,
+        package,
+    )
+    .await;
+    jars.push(dep_path);
+    jars
 }
 
 // donwlads a dependencies dependencies, gets its own function, b/c we don't need to do version resolution
@@ -170,9 +578,12 @@ async fn finish_download_dep(
 async fn download_dep_dep(
     client: Client,
     pom_url: &str,
+    repositories: Arc<[String]>,
+    lib: &Path,
     locked_lock_file: Arc<Mutex<LockFile>>,
     mut package: Package,
-) {
+) -> Vec<PathBuf> {
+    let mut jars = vec![];
     let text = client
         .get(pom_url)
         .send()
@@ -189,11 +600,18 @@ async fn download_dep_dep(
                 && !dep.optional
         });
         for dep in filterdeps.clone() {
-            let req_url = format!(
-                "https://repo1.maven.org/maven2/{}/{}/",
-                dep.group_id.replace('.', "/"),
-                dep.artifact_id,
-            );
+            let req_url = find_artifact(&client, &repositories, &dep.group_id, &dep.artifact_id)
+                .await
+                .map_or_else(
+                    || {
+                        format!(
+                            "{MAVEN_CENTRAL}/{}/{}/",
+                            dep.group_id.replace('.', "/"),
+                            dep.artifact_id,
+                        )
+                    },
+                    |(req_url, _)| req_url,
+                );
             let blend_dep = Package::new(
                 dep.artifact_id.clone(),
                 dep.version.clone(),
@@ -202,21 +620,25 @@ async fn download_dep_dep(
                 None,
                 None,
             );
-            finish_download_dep(
-                dep.artifact_id,
-                &dep.version,
-                req_url,
-                client.clone(),
-                locked_lock_file.clone(),
-                blend_dep,
-            )
-            .await;
+            jars.extend(
+                finish_download_dep(
+                    &dep.version,
+                    req_url,
+                    client.clone(),
+                    repositories.clone(),
+                    lib,
+                    locked_lock_file.clone(),
+                    blend_dep,
+                )
+                .await,
+            );
         }
         // get all parent dependencies as names
         let deps = filterdeps.map(|dep| dep.artifact_id).collect();
         package.set_dependencies(deps);
         write_package_to_lockfile(package, locked_lock_file);
     }
+    jars
 }
 
 fn write_package_to_lockfile(packed: Package, locked_lock_file: Arc<Mutex<LockFile>>) {
@@ -225,8 +647,8 @@ fn write_package_to_lockfile(packed: Package, locked_lock_file: Arc<Mutex<LockFi
     }
 }
 
-fn get_lock_path() -> PathBuf {
-    let mut root = crate::config::get_root_path().unwrap();
+pub fn get_lock_path() -> PathBuf {
+    let mut root = crate::config::shared_root().unwrap();
     root.push("Brew.lock");
     root
 }
@@ -292,7 +714,6 @@ pub struct Dependencies {
 }
 #[derive(Deserialize, Debug, Default, Clone)]
 #[serde(rename_all = "camelCase")]
-
 pub struct Dependency {
     group_id: String,
     artifact_id: String,
@@ -310,7 +731,7 @@ pub struct MavenDependencyScope {
     content: MavenDependencyScopeType,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Default)]
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize, Default)]
 #[serde(rename_all = "camelCase")]
 pub enum MavenDependencyScopeType {
     #[default]
@@ -319,3 +740,50 @@ pub enum MavenDependencyScopeType {
     Test,
     Provided,
 }
+
+impl MavenDependencyScopeType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Compile => "compile",
+            Self::Runtime => "runtime",
+            Self::Test => "test",
+            Self::Provided => "provided",
+        }
+    }
+
+    /// Where the jars of a blend with this scope are fetched to, test and provided blends get their own
+    /// directory in lib so they stay out of everything the brew ships
+    pub fn lib_dir(self, lib: &Path) -> PathBuf {
+        match self {
+            Self::Compile | Self::Runtime => lib.to_path_buf(),
+            Self::Test => lib.join("test"),
+            Self::Provided => lib.join("provided"),
+        }
+    }
+
+    /// The scope a blend used by a blend of this scope ends up with, like in maven a blend's own tests don't
+    /// need to be fetched and whatever a test (or provided) blend uses is only needed for testing (or compiling)
+    fn inherit(self, scope: Self) -> Option<Self> {
+        match (self, scope) {
+            (_, Self::Test) => None,
+            (Self::Test, _) => Some(Self::Test),
+            (Self::Provided, _) | (_, Self::Provided) => Some(Self::Provided),
+            _ => Some(scope),
+        }
+    }
+
+    /// The scope a blend used in both scopes needs, it is shipped if either ships it
+    fn widest(self, other: Self) -> Self {
+        let rank = |scope: Self| match scope {
+            Self::Compile => 0,
+            Self::Runtime => 1,
+            Self::Provided => 2,
+            Self::Test => 3,
+        };
+        if rank(other) < rank(self) {
+            other
+        } else {
+            self
+        }
+    }
+}
@@ -0,0 +1,801 @@
+//! import.rs reads what `barista init` needs to know about an existing java project, from its pom.xml
+//! or (simple, groovy or kotlin dsl) build.gradle when it has one, otherwise from the directory itself
+use std::{
+    collections::HashMap,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use semver::{Version, VersionReq};
+use serde::Deserialize;
+
+use crate::{
+    config::{Bin, BlendConfig},
+    fetch::{to_version, MavenDependencyScopeType},
+    utils::{has_main, walk_dir},
+};
+
+#[derive(Debug, thiserror::Error)]
+pub enum ImportError {
+    #[error("error reading {1}: {0}")]
+    Read(io::Error, String),
+    #[error("error parsing {1}: {0}")]
+    Pom(quick_xml::DeError, String),
+}
+
+/// Everything about a project that ends up in its Brew.toml
+#[derive(Debug)]
+pub struct ImportedProject {
+    pub name: String,
+    pub group: Option<String>,
+    pub version: Version,
+    pub description: Option<String>,
+    /// the source root, if it isn't src
+    pub source: Option<String>,
+    /// the java release the project is compiled for
+    pub release: Option<String>,
+    pub repositories: Vec<String>,
+    pub blends: Vec<(String, BlendConfig)>,
+    pub bins: Vec<Bin>,
+}
+
+/// Reads the project in `root`, `name` is what the project is called when its build doesn't say
+pub fn import(root: &Path, name: String) -> Result<ImportedProject, ImportError> {
+    let mut project = ImportedProject {
+        name,
+        group: None,
+        version: Version::new(0, 1, 0),
+        description: None,
+        source: None,
+        release: None,
+        repositories: vec![],
+        blends: vec![],
+        bins: vec![],
+    };
+    let main_class = if root.join("pom.xml").exists() {
+        import_maven(root, &mut project)?
+    } else if let Some(build) = ["build.gradle", "build.gradle.kts"]
+        .iter()
+        .map(|build| root.join(build))
+        .find(|build| build.exists())
+    {
+        import_gradle(root, &build, &mut project)?
+    } else {
+        None
+    };
+    // maven and gradle both default to the standard directory layout
+    if project.source.is_none() && root.join("src").join("main").join("java").is_dir() {
+        project.source = Some("src/main/java".to_string());
+    }
+    project.bins = find_bins(
+        &root.join(project.source.as_deref().unwrap_or("src")),
+        main_class,
+    );
+    Ok(project)
+}
+
+/// The classes with a main method, or just the main class the build names, unless there is a Main.java to run
+fn find_bins(source_root: &Path, main_class: Option<String>) -> Vec<Bin> {
+    if source_root.join("Main.java").exists() {
+        return vec![];
+    }
+    let main_source = main_class.map(|class| PathBuf::from(class.replace('.', "/") + ".java"));
+    let mut mains = walk_dir(source_root)
+        .into_iter()
+        .filter(|path| path.extension().is_some_and(|ext| ext == "java"))
+        .filter_map(|path| Some(path.strip_prefix(source_root).ok()?.to_path_buf()))
+        .filter(|path| {
+            main_source.as_ref().map_or_else(
+                || fs::read_to_string(source_root.join(path)).is_ok_and(|text| has_main(&text)),
+                |main_source| path == main_source,
+            )
+        })
+        .collect::<Vec<_>>();
+    mains.sort();
+    mains
+        .into_iter()
+        .filter_map(|path| {
+            let name = path.file_stem()?.to_string_lossy().to_lowercase();
+            Some(Bin::new(name, path.to_string_lossy().replace('\\', "/")))
+        })
+        .collect()
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+struct Pom {
+    group_id: Option<String>,
+    artifact_id: Option<String>,
+    version: Option<String>,
+    description: Option<String>,
+    parent: Option<PomParent>,
+    #[serde(default)]
+    properties: HashMap<String, String>,
+    dependency_management: Option<PomDependencyManagement>,
+    dependencies: Option<PomDependencies>,
+    repositories: Option<PomRepositories>,
+    build: Option<PomBuild>,
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+struct PomParent {
+    group_id: Option<String>,
+    version: Option<String>,
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+struct PomDependencyManagement {
+    dependencies: Option<PomDependencies>,
+}
+
+#[derive(Deserialize, Debug, Default)]
+struct PomDependencies {
+    #[serde(default)]
+    dependency: Vec<PomDependency>,
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+struct PomDependency {
+    group_id: String,
+    artifact_id: String,
+    version: Option<String>,
+    scope: Option<String>,
+}
+
+#[derive(Deserialize, Debug, Default)]
+struct PomRepositories {
+    #[serde(default)]
+    repository: Vec<PomRepository>,
+}
+
+#[derive(Deserialize, Debug, Default)]
+struct PomRepository {
+    url: String,
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+struct PomBuild {
+    source_directory: Option<String>,
+    plugins: Option<PomPlugins>,
+}
+
+#[derive(Deserialize, Debug, Default)]
+struct PomPlugins {
+    #[serde(default)]
+    plugin: Vec<PomPlugin>,
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+struct PomPlugin {
+    artifact_id: String,
+    configuration: Option<PomPluginConfiguration>,
+}
+
+/// The parts of the compiler, jar and exec plugins' configuration that matter to a brew
+#[derive(Deserialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+struct PomPluginConfiguration {
+    release: Option<String>,
+    source: Option<String>,
+    target: Option<String>,
+    main_class: Option<String>,
+    archive: Option<PomArchive>,
+}
+
+#[derive(Deserialize, Debug, Default)]
+struct PomArchive {
+    manifest: Option<PomManifest>,
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+struct PomManifest {
+    main_class: Option<String>,
+}
+
+/// Fills in the project from its pom.xml, returning the main class if one of the plugins names it
+fn import_maven(root: &Path, project: &mut ImportedProject) -> Result<Option<String>, ImportError> {
+    let path = root.join("pom.xml");
+    let text =
+        fs::read_to_string(&path).map_err(|e| ImportError::Read(e, path.display().to_string()))?;
+    let pom: Pom = quick_xml::de::from_str(&text)
+        .map_err(|e| ImportError::Pom(e, path.display().to_string()))?;
+
+    let parent = pom.parent.unwrap_or_default();
+    let group = pom.group_id.or(parent.group_id);
+    let version = pom.version.or(parent.version);
+    let mut properties = pom.properties;
+    for (key, value) in [
+        ("project.groupId", &group),
+        ("project.version", &version),
+        ("project.artifactId", &pom.artifact_id),
+    ] {
+        if let Some(value) = value {
+            properties.insert(key.to_string(), value.clone());
+        }
+    }
+    let resolve = |value: &str| interpolate(value, &properties);
+
+    if let Some(name) = &pom.artifact_id {
+        project.name = resolve(name);
+    }
+    project.group = group.as_deref().map(resolve);
+    if let Some(version) = version
+        .as_deref()
+        .and_then(|version| parse_version(&resolve(version)))
+    {
+        project.version = version;
+    }
+    project.description = pom
+        .description
+        .as_deref()
+        .map(|description| description.split_whitespace().collect::<Vec<_>>().join(" "));
+
+    let managed = pom
+        .dependency_management
+        .and_then(|management| management.dependencies)
+        .map(|dependencies| dependencies.dependency)
+        .unwrap_or_default();
+    for dependency in pom
+        .dependencies
+        .map(|dependencies| dependencies.dependency)
+        .unwrap_or_default()
+    {
+        let version = dependency.version.clone().or_else(|| {
+            managed
+                .iter()
+                .find(|managed| {
+                    managed.group_id == dependency.group_id
+                        && managed.artifact_id == dependency.artifact_id
+                })
+                .and_then(|managed| managed.version.clone())
+        });
+        let scope = match dependency.scope.as_deref() {
+            Some("runtime") => MavenDependencyScopeType::Runtime,
+            Some("test") => MavenDependencyScopeType::Test,
+            Some("provided") => MavenDependencyScopeType::Provided,
+            _ => MavenDependencyScopeType::Compile,
+        };
+        project.blends.push((
+            resolve(&dependency.artifact_id),
+            BlendConfig::new_maven(
+                version_req(version.as_deref().map(resolve).as_deref()),
+                resolve(&dependency.group_id),
+            )
+            .with_scope(scope),
+        ));
+    }
+    project.repositories = pom
+        .repositories
+        .map(|repositories| repositories.repository)
+        .unwrap_or_default()
+        .into_iter()
+        .map(|repository| resolve(repository.url.trim()))
+        .filter(|url| url.starts_with("http"))
+        .collect();
+
+    let build = pom.build.unwrap_or_default();
+    project.source = build.source_directory.as_deref().map(resolve);
+    let plugins = build
+        .plugins
+        .map(|plugins| plugins.plugin)
+        .unwrap_or_default();
+    let configuration = |artifact: &str| {
+        plugins
+            .iter()
+            .find(|plugin| plugin.artifact_id == artifact)
+            .and_then(|plugin| plugin.configuration.as_ref())
+    };
+    let compiler = configuration("maven-compiler-plugin");
+    project.release = compiler
+        .and_then(|compiler| {
+            compiler
+                .release
+                .as_ref()
+                .or(compiler.target.as_ref())
+                .or(compiler.source.as_ref())
+        })
+        .cloned()
+        .or_else(|| {
+            [
+                "maven.compiler.release",
+                "maven.compiler.target",
+                "maven.compiler.source",
+                "java.version",
+            ]
+            .iter()
+            .find_map(|key| properties.get(*key).cloned())
+        })
+        .map(|release| java_release(&resolve(&release)));
+    let main_class = configuration("maven-jar-plugin")
+        .and_then(|jar| jar.archive.as_ref()?.manifest.as_ref()?.main_class.clone())
+        .or_else(|| configuration("exec-maven-plugin")?.main_class.clone());
+    Ok(main_class.map(|main_class| resolve(&main_class)))
+}
+
+/// Replaces `${property}` with the property's value, leaving unknown properties as they are
+fn interpolate(value: &str, properties: &HashMap<String, String>) -> String {
+    let mut out = String::new();
+    let mut rest = value.trim();
+    while let Some(start) = rest.find("${") {
+        let Some(end) = rest[start..].find('}') else {
+            break;
+        };
+        out += &rest[..start];
+        let key = &rest[start + 2..start + end];
+        match properties.get(key) {
+            Some(value) => out += value,
+            None => out += &rest[start..=start + end],
+        }
+        rest = &rest[start + end + 1..];
+    }
+    out + rest
+}
+
+/// Fills in the project from its build.gradle, returning the main class if the application plugin names it
+fn import_gradle(
+    root: &Path,
+    build: &Path,
+    project: &mut ImportedProject,
+) -> Result<Option<String>, ImportError> {
+    let text =
+        fs::read_to_string(build).map_err(|e| ImportError::Read(e, build.display().to_string()))?;
+    for settings in ["settings.gradle", "settings.gradle.kts"] {
+        if let Ok(settings) = fs::read_to_string(root.join(settings)) {
+            if let Some(name) = settings
+                .lines()
+                .find_map(|line| assignment(line, "rootProject.name"))
+            {
+                project.name = name;
+            }
+        }
+    }
+
+    let mut main_class = None;
+    // the blocks the current line is in, ie ["dependencies"] or ["repositories", "maven"]
+    let mut blocks: Vec<String> = vec![];
+    for line in text.lines() {
+        // a comment starts the line or follows whitespace, unlike the // in a url
+        let line = match line.trim().split_once(" //") {
+            _ if line.trim().starts_with("//") => "",
+            Some((code, _)) => code.trim(),
+            None => line.trim(),
+        };
+        let block = blocks.last().map(String::as_str);
+        if let Some(group) = assignment(line, "group") {
+            project.group = Some(group);
+        } else if let Some(version) = assignment(line, "version").filter(|_| blocks.is_empty()) {
+            if let Some(version) = parse_version(&version) {
+                project.version = version;
+            }
+        } else if let Some(description) = assignment(line, "description") {
+            project.description = Some(description);
+        } else if let Some(class) =
+            assignment(line, "mainClass").or_else(|| assignment(line, "mainClassName"))
+        {
+            main_class = Some(class);
+        } else if let Some(release) = gradle_release(line) {
+            project.release = Some(release);
+        } else if block == Some("dependencies") {
+            if let Some(blend) = gradle_dependency(line) {
+                project.blends.push(blend);
+            }
+        } else if line.starts_with("maven") || block == Some("maven") {
+            // maven { url 'https://...' } or maven("https://...")
+            if let Some(url) = quoted(line.split_once("url").map_or(line, |(_, url)| url))
+                .filter(|url| url.starts_with("http"))
+            {
+                project.repositories.push(url);
+            }
+        }
+
+        // one-line blocks (maven { url '...' }) open and close on the same line
+        let name = line.split(['{', '(', ' ']).next().unwrap_or_default();
+        for c in line.chars() {
+            match c {
+                '{' => blocks.push(name.to_string()),
+                '}' => {
+                    blocks.pop();
+                }
+                _ => {}
+            }
+        }
+    }
+    Ok(main_class)
+}
+
+/// The value of `key = 'value'`, `key = "value"`, `key 'value'` or `key.set("value")`
+fn assignment(line: &str, key: &str) -> Option<String> {
+    let rest = line.strip_prefix(key)?;
+    // ie groupId or mainClassName, not group or mainClass
+    if rest.starts_with(|c: char| c.is_alphanumeric() || c == '_') {
+        return None;
+    }
+    quoted(rest)
+}
+
+/// The first string literal in `text`
+fn quoted(text: &str) -> Option<String> {
+    let start = text.find(['\'', '"'])?;
+    let quote = text[start..].chars().next()?;
+    let end = text[start + 1..].find(quote)?;
+    Some(text[start + 1..start + 1 + end].to_string())
+}
+
+/// `implementation 'group:name:version'` or `testImplementation("group:name:version")`
+fn gradle_dependency(line: &str) -> Option<(String, BlendConfig)> {
+    let configuration = line.split(['(', ' ', '\'', '"']).next()?;
+    let scope = match configuration {
+        "implementation" | "api" | "compile" => MavenDependencyScopeType::Compile,
+        "runtimeOnly" | "runtime" => MavenDependencyScopeType::Runtime,
+        "compileOnly" => MavenDependencyScopeType::Provided,
+        "testImplementation" | "testRuntimeOnly" | "testCompileOnly" => {
+            MavenDependencyScopeType::Test
+        }
+        _ => return None,
+    };
+    // a platform (bom) only manages versions, it has no jar to fetch
+    if line.contains("platform(") {
+        return None;
+    }
+    let coordinates = quoted(line)?;
+    let mut parts = coordinates.split(':');
+    let (group, name, version) = (parts.next()?, parts.next()?, parts.next());
+    Some((
+        name.to_string(),
+        BlendConfig::new_maven(version_req(version), group.to_string()).with_scope(scope),
+    ))
+}
+
+/// The java release from `sourceCompatibility = JavaVersion.VERSION_17`, `JavaLanguageVersion.of(17)`
+/// or `options.release = 17`
+fn gradle_release(line: &str) -> Option<String> {
+    let release = if let Some((_, version)) = line.split_once("JavaLanguageVersion.of(") {
+        version
+    } else if line.starts_with("sourceCompatibility")
+        || line.starts_with("targetCompatibility")
+        || line.starts_with("options.release")
+        || line.starts_with("java.sourceCompatibility")
+    {
+        let (_, version) = line
+            .split_once(['=', '('])
+            .or_else(|| line.split_once(' '))?;
+        version.rsplit("VERSION_").next()?
+    } else {
+        return None;
+    };
+    let release = release
+        .trim_start_matches([' ', '\'', '"'])
+        .split(|c: char| !(c.is_ascii_digit() || c == '.' || c == '_'))
+        .next()?
+        .replace('_', ".");
+    (!release.is_empty()).then(|| java_release(&release))
+}
+
+/// 1.8 is java 8
+fn java_release(release: &str) -> String {
+    let release = release.trim();
+    release.strip_prefix("1.").unwrap_or(release).to_string()
+}
+
+fn parse_version(version: &str) -> Option<Version> {
+    lenient_semver::Version::parse(version).ok().map(to_version)
+}
+
+/// Maven versions are exact, barista takes compatible updates of them, ranges and unknown versions match any version
+fn version_req(version: Option<&str>) -> VersionReq {
+    version
+        .and_then(parse_version)
+        .and_then(|version| VersionReq::parse(&format!("^{version}")).ok())
+        .unwrap_or(VersionReq::STAR)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Imports a project made of the given files, from a directory of its own under the temp dir
+    fn import_files(test: &str, files: &[(&str, &str)]) -> ImportedProject {
+        let root =
+            std::env::temp_dir().join(format!("barista-import-test-{}-{test}", std::process::id()));
+        for (path, text) in files {
+            let path = root.join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, text).unwrap();
+        }
+        let project = import(&root, "fallback".to_string());
+        fs::remove_dir_all(&root).unwrap();
+        project.unwrap()
+    }
+
+    fn blend<'a>(project: &'a ImportedProject, name: &str) -> &'a BlendConfig {
+        &project
+            .blends
+            .iter()
+            .find(|(blend, _)| blend == name)
+            .unwrap_or_else(|| panic!("no blend {name} in {:?}", project.blends))
+            .1
+    }
+
+    const POM: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<project xmlns="http://maven.apache.org/POM/4.0.0">
+  <modelVersion>4.0.0</modelVersion>
+  <parent>
+    <groupId>com.example</groupId>
+    <artifactId>parent</artifactId>
+    <version>2.1.0</version>
+  </parent>
+  <artifactId>${app.name}</artifactId>
+  <description>
+    An app
+    that does things
+  </description>
+  <properties>
+    <app.name>example-app</app.name>
+    <maven.compiler.source>1.8</maven.compiler.source>
+    <gson.version>2.10.1</gson.version>
+  </properties>
+  <dependencyManagement>
+    <dependencies>
+      <dependency>
+        <groupId>org.slf4j</groupId>
+        <artifactId>slf4j-api</artifactId>
+        <version>2.0.9</version>
+      </dependency>
+    </dependencies>
+  </dependencyManagement>
+  <dependencies>
+    <dependency>
+      <groupId>com.google.code.gson</groupId>
+      <artifactId>gson</artifactId>
+      <version>${gson.version}</version>
+    </dependency>
+    <dependency>
+      <groupId>org.slf4j</groupId>
+      <artifactId>slf4j-api</artifactId>
+    </dependency>
+    <dependency>
+      <groupId>${project.groupId}</groupId>
+      <artifactId>core</artifactId>
+      <version>${project.version}</version>
+      <scope>provided</scope>
+    </dependency>
+    <dependency>
+      <groupId>junit</groupId>
+      <artifactId>junit</artifactId>
+      <version>4.13.2</version>
+      <scope>test</scope>
+    </dependency>
+  </dependencies>
+  <repositories>
+    <repository>
+      <url> https://repo.example.com/maven2 </url>
+    </repository>
+  </repositories>
+  <build>
+    <plugins>
+      <plugin>
+        <artifactId>maven-jar-plugin</artifactId>
+        <configuration>
+          <archive>
+            <manifest>
+              <mainClass>com.example.App</mainClass>
+            </manifest>
+          </archive>
+        </configuration>
+      </plugin>
+    </plugins>
+  </build>
+</project>
+"#;
+
+    const APP: &str = "package com.example;\n\npublic class App {\n    public static void main(String[] args) {}\n}\n";
+
+    #[test]
+    fn maven_interpolates_properties() {
+        let project = import_files(
+            "maven-properties",
+            &[
+                ("pom.xml", POM),
+                ("src/main/java/com/example/App.java", APP),
+            ],
+        );
+        assert_eq!(project.name, "example-app");
+        assert_eq!(project.group.as_deref(), Some("com.example"));
+        assert_eq!(project.version, Version::new(2, 1, 0));
+        assert_eq!(
+            project.description.as_deref(),
+            Some("An app that does things")
+        );
+        assert_eq!(project.source.as_deref(), Some("src/main/java"));
+        assert_eq!(
+            project.repositories,
+            ["https://repo.example.com/maven2".to_string()]
+        );
+        let gson = blend(&project, "gson");
+        assert_eq!(gson.author().unwrap(), "com.google.code.gson");
+        assert_eq!(gson.version().to_string(), "^2.10.1");
+        let core = blend(&project, "core");
+        assert_eq!(core.author().unwrap(), "com.example");
+        assert_eq!(core.version().to_string(), "^2.1.0");
+        assert_eq!(core.scope(), MavenDependencyScopeType::Provided);
+        assert_eq!(
+            blend(&project, "junit").scope(),
+            MavenDependencyScopeType::Test
+        );
+        let bins = project.bins.iter().map(Bin::source).collect::<Vec<_>>();
+        assert_eq!(bins, [PathBuf::from("com/example/App.java")]);
+    }
+
+    #[test]
+    fn maven_takes_versions_from_dependency_management() {
+        let project = import_files("maven-managed", &[("pom.xml", POM)]);
+        assert_eq!(blend(&project, "slf4j-api").version().to_string(), "^2.0.9");
+    }
+
+    #[test]
+    fn maven_release_from_compiler_properties() {
+        let project = import_files("maven-release", &[("pom.xml", POM)]);
+        assert_eq!(project.release.as_deref(), Some("8"));
+    }
+
+    #[test]
+    fn interpolate_leaves_unknown_properties() {
+        let properties = HashMap::from([("a".to_string(), "1".to_string())]);
+        assert_eq!(interpolate(" ${a}.${b}-${a} ", &properties), "1.${b}-1");
+        assert_eq!(interpolate("${a", &properties), "${a");
+    }
+
+    const GROOVY: &str = r#"plugins {
+    id 'java'
+    id 'application'
+}
+
+group = 'com.example'
+version = '1.2.0'
+description = "An app"
+sourceCompatibility = JavaVersion.VERSION_1_8
+
+repositories {
+    mavenCentral()
+    maven { url 'https://repo.example.com/releases' } // our releases
+}
+
+dependencies {
+    implementation platform('org.springframework.boot:spring-boot-dependencies:3.2.0')
+    implementation 'com.google.code.gson:gson:2.10.1' // see https://github.com/google/gson
+    // implementation 'org.commented:out:1.0'
+    compileOnly 'org.projectlombok:lombok:1.18.30'
+    testImplementation "junit:junit:4.13.2"
+}
+
+application {
+    mainClassName = 'com.example.App'
+}
+"#;
+
+    #[test]
+    fn gradle_groovy_dsl() {
+        let project = import_files(
+            "gradle-groovy",
+            &[
+                ("build.gradle", GROOVY),
+                ("settings.gradle", "rootProject.name = 'groovy-app'\n"),
+                ("src/main/java/com/example/App.java", APP),
+            ],
+        );
+        assert_eq!(project.name, "groovy-app");
+        assert_eq!(project.group.as_deref(), Some("com.example"));
+        assert_eq!(project.version, Version::new(1, 2, 0));
+        assert_eq!(project.description.as_deref(), Some("An app"));
+        assert_eq!(project.release.as_deref(), Some("8"));
+        assert_eq!(
+            project.repositories,
+            ["https://repo.example.com/releases".to_string()]
+        );
+        let names = project
+            .blends
+            .iter()
+            .map(|(name, _)| name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(names, ["gson", "lombok", "junit"]);
+        assert_eq!(blend(&project, "gson").version().to_string(), "^2.10.1");
+        assert_eq!(
+            blend(&project, "lombok").scope(),
+            MavenDependencyScopeType::Provided
+        );
+        assert_eq!(
+            blend(&project, "junit").scope(),
+            MavenDependencyScopeType::Test
+        );
+        let bins = project.bins.iter().map(Bin::source).collect::<Vec<_>>();
+        assert_eq!(bins, [PathBuf::from("com/example/App.java")]);
+    }
+
+    const KOTLIN: &str = r#"plugins {
+    application
+}
+
+group = "com.example"
+version = "0.3.0"
+
+repositories {
+    mavenCentral()
+    maven("https://jitpack.io")
+}
+
+dependencies {
+    implementation("org.slf4j:slf4j-api:2.0.9")
+    runtimeOnly("org.slf4j:slf4j-simple:2.0.9")
+    testImplementation(platform("org.junit:junit-bom:5.10.0"))
+    testImplementation("org.junit.jupiter:junit-jupiter")
+}
+
+java {
+    toolchain {
+        languageVersion.set(JavaLanguageVersion.of(21))
+    }
+}
+
+application {
+    mainClass.set("com.example.App")
+}
+"#;
+
+    #[test]
+    fn gradle_kotlin_dsl() {
+        let project = import_files(
+            "gradle-kotlin",
+            &[
+                ("build.gradle.kts", KOTLIN),
+                ("settings.gradle.kts", "rootProject.name = \"kotlin-app\"\n"),
+                ("src/main/java/com/example/App.java", APP),
+                ("src/main/java/com/example/Tool.java", APP),
+            ],
+        );
+        assert_eq!(project.name, "kotlin-app");
+        assert_eq!(project.group.as_deref(), Some("com.example"));
+        assert_eq!(project.version, Version::new(0, 3, 0));
+        assert_eq!(project.release.as_deref(), Some("21"));
+        assert_eq!(project.repositories, ["https://jitpack.io".to_string()]);
+        assert_eq!(
+            blend(&project, "slf4j-simple").scope(),
+            MavenDependencyScopeType::Runtime
+        );
+        // no version means whatever the bom says, which barista doesn't read
+        assert_eq!(
+            blend(&project, "junit-jupiter").version(),
+            &VersionReq::STAR
+        );
+        assert!(!project.blends.iter().any(|(name, _)| name == "junit-bom"));
+        // only the main class the build names is a bin, not every class with a main method
+        let bins = project.bins.iter().map(Bin::source).collect::<Vec<_>>();
+        assert_eq!(bins, [PathBuf::from("com/example/App.java")]);
+    }
+
+    #[test]
+    fn gradle_release_forms() {
+        assert_eq!(
+            gradle_release("sourceCompatibility = JavaVersion.VERSION_1_8").as_deref(),
+            Some("8")
+        );
+        assert_eq!(
+            gradle_release("targetCompatibility = JavaVersion.VERSION_17").as_deref(),
+            Some("17")
+        );
+        assert_eq!(
+            gradle_release("sourceCompatibility = '11'").as_deref(),
+            Some("11")
+        );
+        assert_eq!(
+            gradle_release("options.release = 21").as_deref(),
+            Some("21")
+        );
+        assert_eq!(gradle_release("version = '1.0'"), None);
+    }
+}
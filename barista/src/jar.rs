@@ -1,61 +1,622 @@
-use std::process::{Command, Stdio};
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    env,
+    ffi::OsString,
+    fs::{self, File},
+    io::{ErrorKind, Read, Write},
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+};
 
-use javaup::config;
+use zip::{
+    result::ZipError, write::FileOptions, CompressionMethod, DateTime, ZipArchive, ZipWriter,
+};
 
-use crate::{config::Config, utils::unless_exists};
+use crate::{
+    config::{Bin, BinNotFoundError, Config, JarConfig, Lib, ResourcesConfig, ShadeConfig},
+    fetch::{get_lock_path, MavenDependencyScopeType},
+    link::IMAGE_DIR,
+    lock::LockFile,
+    package::PACKAGE_DIR,
+    shade::Relocator,
+    sip::TEST_DIR,
+    toolchain,
+    utils::{civil_time, open_toml, relative_path, unless_exists, walk_dir},
+};
+
+/// Where in `bin` a brew used as a blend compiles its library to
+pub const LIB_CLASSES_DIR: &str = "lib-classes";
 
 impl Config {
-    pub fn jar(&self, path: &str) {
-        self.fetch();
-        let binding = crate::config::get_root_path().unwrap();
-        let root = binding.display();
-        let mut java_bin = config::jdkdir();
-
-        let java_config = config::config_file();
-        java_bin.push(java_config.default_jdk.clone().unwrap().distribution);
-        java_bin.push(java_config.default_jdk.unwrap().version);
-        java_bin.push("bin");
-        let mut jar_bin = java_bin.clone();
-        #[cfg(target_os = "windows")]
-        {
-            jar_bin.push("jar.exe");
-            java_bin.push("javac.exe");
+    /// Builds the library of a brew used as a blend into its own `jar` directory and copies the jar into
+    /// each of `libs`, the directories in lib for the scopes the blend is used in, which already have
+    /// every blend the library needs
+    pub fn jar(&self, path: &Path, libs: &BTreeSet<PathBuf>) {
+        let artifact = self.library_jar(path);
+        for lib in libs {
+            unless_exists(lib, || {
+                fs::create_dir_all(lib)
+                    .unwrap_or_else(|e| panic!("could not create {}: {e}", lib.display()))
+            });
+            let blend = lib.join(artifact.file_name().unwrap());
+            fs::copy(&artifact, &blend).unwrap_or_else(|e| {
+                panic!(
+                    "could not copy {} to {}: {e}",
+                    artifact.display(),
+                    blend.display()
+                )
+            });
         }
-        #[cfg(not(target_os = "windows"))]
-        {
-            java_bin.push("javac");
-            jar_bin.push("jar");
+    }
+
+    /// Builds the library of the brew in `path` into `jar/{name}-{version}.jar`, the jar it is published as,
+    /// without a `Main-Class` or `Class-Path`
+    pub fn library_jar(&self, path: &Path) -> PathBuf {
+        let jdk = toolchain::find(self).unwrap_or_else(|e| panic!("{e}"));
+        let default_lib = Lib::default();
+        let lib = self.lib().unwrap_or(&default_lib);
+        let source_roots = self.library_roots(path);
+        let (sources, resources): (Vec<_>, Vec<_>) = self
+            .library_files(path)
+            .into_iter()
+            .partition(|file| file.extension().is_some_and(|ext| ext == "java"));
+        if sources.is_empty() {
+            panic!(
+                "no java sources found for library {} in {}",
+                self.brew().name(),
+                source_roots[0].display()
+            )
         }
-        let mut binding = Command::new(java_bin);
-        unless_exists(format!("{path}/src/Library.java"), || {
-            panic!("not bin target found")
-        });
+        let classes = path.join("bin").join(LIB_CLASSES_DIR);
+        // start from an empty class tree so classes of deleted sources don't end up in the jar
+        if let Err(e) = fs::remove_dir_all(&classes) {
+            if e.kind() != ErrorKind::NotFound {
+                panic!("could not clean {}: {e}", classes.display())
+            }
+        }
+        let mut binding = Command::new(jdk.javac());
         let javac_ex = binding
+            .args(toolchain::release_args(&jdk))
             .arg("-cp")
-            .arg(format!("{root}/lib/*",))
+            .arg(self.lib_class_path())
             .arg("--source-path")
-            .arg(format!("{path}/src"))
-            .arg(format!("{path}/src/Library.java"))
+            .arg(env::join_paths(&source_roots).expect("brew path can't be used in a source path"))
             .arg("-d")
-            .arg(format!("{root}/bin/{}/", self.brew().name()))
+            .arg(&classes)
+            .args(&sources)
             .stdout(Stdio::inherit())
             .stderr(Stdio::inherit());
 
-        javac_ex.status().unwrap();
-        let mut binding = Command::new(jar_bin);
-        let bindings = binding.current_dir(format!("bin"));
-        bindings
-            .arg("-cf")
-            .arg(format!(
-                "../lib/{}-{}.{}",
-                self.brew().name(),
-                self.brew().version(),
-                "jar"
-            ))
-            .arg(self.brew().name())
-            .stdout(Stdio::inherit())
-            .stderr(Stdio::inherit())
-            .status()
+        if !javac_ex.status().is_ok_and(|status| status.success()) {
+            panic!("could not compile library {}", self.brew().name())
+        }
+        let exports = lib.exports().map(|exports| {
+            for export in exports {
+                unless_exists(classes.join(export.replace('.', "/")), || {
+                    panic!(
+                        "library {} exports {export} but has no classes in that package",
+                        self.brew().name()
+                    )
+                });
+            }
+            exports.join(",")
+        });
+        let mut manifest = manifest(None, &[]);
+        if let Some(exports) = exports {
+            manifest += &manifest_line("Export-Package", &exports);
+        }
+        let mut jar = JarBuilder::new(manifest);
+        jar.add_dir(&classes);
+        // anything that isn't java source in the source root is a resource the library needs at runtime
+        for resource in resources {
+            let name = entry_name(
+                source_roots
+                    .iter()
+                    .find_map(|source_root| resource.strip_prefix(source_root).ok())
+                    .unwrap(),
+            );
+            let bytes = fs::read(&resource)
+                .unwrap_or_else(|e| panic!("could not read {}: {e}", resource.display()));
+            jar.insert(name, bytes);
+        }
+        for (resource, bytes) in self.resource_files(path) {
+            jar.insert(entry_name(&resource), bytes);
+        }
+        self.add_pom_properties(&mut jar);
+        let name = format!("{}-{}.jar", self.brew().name(), self.brew().version());
+        let jar_dir = path.join("jar");
+        fs::create_dir_all(&jar_dir)
+            .unwrap_or_else(|e| panic!("could not create {}: {e}", jar_dir.display()));
+        let artifact = jar_dir.join(name);
+        jar.write(&artifact, self.jar_timestamp());
+        artifact
+    }
+
+    /// The source roots of the library, `[lib] path` (or the brew's source root) and those of its features
+    pub fn library_roots(&self, path: &Path) -> Vec<PathBuf> {
+        let src = self
+            .lib()
+            .and_then(Lib::path)
+            .map_or(self.brew().source(), String::as_str);
+        self.source_roots(path, src)
+    }
+
+    /// Every file in the library's source roots but the tests and resources, and the bins when the brew has a `[lib]`
+    pub fn library_files(&self, path: &Path) -> Vec<PathBuf> {
+        let default_resources = ResourcesConfig::default();
+        let resource_root = path.join(self.resources().unwrap_or(&default_resources).path());
+        let test_root = self.test_root(path);
+        let source_root = self.source_root(path);
+        let bins = if self.lib().is_some() {
+            self.bins(&source_root)
+                .iter()
+                .map(Bin::source)
+                .chain([PathBuf::from("Main.java")])
+                .map(|bin| source_root.join(bin))
+                .collect()
+        } else {
+            vec![]
+        };
+        self.library_roots(path)
+            .iter()
+            .flat_map(|source_root| walk_dir(source_root))
+            // the resource directory usually lives inside src, its files are added on their own
+            .filter(|file| !file.starts_with(&resource_root) && !file.starts_with(&test_root))
+            .filter(|file| !bins.contains(file))
+            .collect()
+    }
+}
+
+impl Config {
+    /// Packages the roasted classes in `bin` into `jar/{name}-{version}.jar` with a manifest
+    /// pointing at the selected bin, so the brew can be run with `java -jar`, a library is
+    /// packaged the way it is published instead
+    pub fn bin_jar(&self, bin: Option<String>) -> Result<(), BinNotFoundError> {
+        let root = crate::config::get_root_path().unwrap();
+        if self.is_library(bin.as_deref(), &self.source_root(&root)) {
+            self.library_jar(&root);
+            return Ok(());
+        }
+        let main_class = self.main_class(bin, &self.source_root(&root))?;
+        // the jar lives in jar/ so the dependencies are found relative to it in ../lib (or the workspace's lib)
+        let lib = relative_path(&root.join("jar"), &crate::config::get_lib_path().unwrap());
+        let class_path = self
+            .lib_jars()
+            .iter()
+            .filter_map(|jar| jar.file_name())
+            .map(|name| entry_name(&lib.join(name)))
+            .collect::<Vec<_>>();
+        let mut jar = JarBuilder::new(manifest(Some(&main_class), &class_path));
+        jar.add_files(&root.join("bin"), roasted_files(&root.join("bin")));
+        self.add_pom_properties(&mut jar);
+        jar.write(&self.artifact_path(None), self.jar_timestamp());
+        Ok(())
+    }
+
+    /// Packages the roasted classes together with every blend in `lib` into `jar/{name}-{version}-all.jar`,
+    /// relocating packages according to `[shade]`
+    pub fn fat_jar(&self, bin: Option<String>) -> Result<(), BinNotFoundError> {
+        let root = crate::config::get_root_path().unwrap();
+        let no_relocations = HashMap::new();
+        let relocator = Relocator::new(
+            self.shade()
+                .map_or(&no_relocations, ShadeConfig::relocations),
+        );
+        let mut entries = FatEntries::default();
+
+        // the brew's own files go in first so they win over anything a blend ships under the same name
+        let classes = root.join("bin");
+        for path in roasted_files(&classes) {
+            let name = entry_name(path.strip_prefix(&classes).unwrap());
+            let bytes = fs::read(&path)
+                .unwrap_or_else(|e| panic!("could not read {}: {e}", path.display()));
+            entries.add(&relocator, name, bytes);
+        }
+        for jar in self.lib_jars() {
+            let mut archive = File::open(&jar)
+                .map_err(ZipError::Io)
+                .and_then(ZipArchive::new)
+                .unwrap_or_else(|e| panic!("could not open blend {}: {e}", jar.display()));
+            for i in 0..archive.len() {
+                let mut file = archive.by_index(i).unwrap();
+                if file.is_dir() {
+                    continue;
+                }
+                let name = file.name().to_string();
+                let mut bytes = Vec::with_capacity(file.size() as usize);
+                file.read_to_end(&mut bytes).unwrap_or_else(|e| {
+                    panic!("could not read {name} from {}: {e}", jar.display())
+                });
+                entries.add(&relocator, name, bytes);
+            }
+        }
+
+        // a library's fat jar has nothing to run
+        let main_class = (!self.is_library(bin.as_deref(), &self.source_root(&root)))
+            .then(|| self.main_class(bin, &self.source_root(&root)))
+            .transpose()?;
+        let mut jar = JarBuilder::new(manifest(main_class.as_deref(), &[]));
+        for (name, bytes) in entries.finish() {
+            jar.insert(name, bytes);
+        }
+        jar.write(&self.artifact_path(Some("all")), self.jar_timestamp());
+        Ok(())
+    }
+
+    /// Packages the java sources in `src` into `jar/{name}-{version}-sources.jar`, leaving out the tests and resources
+    pub fn sources_jar(&self) {
+        let root = crate::config::get_root_path().unwrap();
+        let source_root = self.source_root(&root);
+        let test_root = self.test_root(&root);
+        let default_resources = ResourcesConfig::default();
+        let resource_root = root.join(self.resources().unwrap_or(&default_resources).path());
+        let sources = walk_dir(&source_root)
+            .into_iter()
+            .filter(|file| !file.starts_with(&test_root) && !file.starts_with(&resource_root))
+            .collect();
+        let mut jar = JarBuilder::new(manifest(None, &[]));
+        jar.add_files(&source_root, sources);
+        jar.write(&self.artifact_path(Some("sources")), self.jar_timestamp());
+    }
+
+    /// Packages the documentation `menu` made in `doc` into `jar/{name}-{version}-javadoc.jar`
+    pub fn javadoc_jar(&self) {
+        let root = crate::config::get_root_path().unwrap();
+        let mut jar = JarBuilder::new(manifest(None, &[]));
+        jar.add_dir(&root.join("doc"));
+        jar.write(&self.artifact_path(Some("javadoc")), self.jar_timestamp());
+    }
+
+    /// Where the jar with the given classifier goes (`jar/{name}-{version}[-{classifier}].jar`),
+    /// the jar directory is created if needed
+    pub fn artifact_path(&self, classifier: Option<&str>) -> PathBuf {
+        let jar_dir = crate::config::get_root_path().unwrap().join("jar");
+        unless_exists(&jar_dir, || {
+            fs::create_dir_all(&jar_dir)
+                .expect("Failed to create Brew jar directory (jar) when packaging")
+        });
+        let classifier = classifier.map_or(String::new(), |classifier| format!("-{classifier}"));
+        jar_dir.join(format!(
+            "{}-{}{classifier}.jar",
+            self.brew().name(),
+            self.brew().version()
+        ))
+    }
+
+    /// Gives the jar the same maven identity a jar built by maven has, so tools can tell which artifact it is
+    fn add_pom_properties(&self, jar: &mut JarBuilder) {
+        let brew = self.brew();
+        jar.insert(
+            format!(
+                "META-INF/maven/{}/{}/pom.properties",
+                brew.group(),
+                brew.name()
+            ),
+            format!(
+                "groupId={}\nartifactId={}\nversion={}\n",
+                brew.group(),
+                brew.name(),
+                brew.version()
+            )
+            .into_bytes(),
+        );
+    }
+
+    /// The time stamped on every jar entry, `SOURCE_DATE_EPOCH` wins over `[jar] timestamp` so
+    /// reproducible build tooling can pin it, without either the earliest time a zip can hold is used
+    fn jar_timestamp(&self) -> DateTime {
+        let epoch = std::env::var("SOURCE_DATE_EPOCH")
+            .ok()
+            .map(|epoch| {
+                epoch
+                    .trim()
+                    .parse()
+                    .unwrap_or_else(|_| panic!("SOURCE_DATE_EPOCH is not a number: {epoch}"))
+            })
+            .or_else(|| self.jar_config().and_then(JarConfig::timestamp))
+            .unwrap_or(0);
+        zip_time(epoch)
+    }
+
+    /// The blend jars in `lib` the brew needs at runtime, sorted so that packaging is stable,
+    /// test and provided blends are left out since they aren't shipped with the brew
+    pub fn lib_jars(&self) -> Vec<PathBuf> {
+        self.jars_in(MavenDependencyScopeType::Compile)
+    }
+
+    /// The blend jars the brew is compiled against, its runtime blends and provided blends
+    pub fn compile_jars(&self) -> Vec<PathBuf> {
+        let mut jars = self.lib_jars();
+        jars.extend(self.jars_in(MavenDependencyScopeType::Provided));
+        jars
+    }
+
+    /// The blend jars the tests are compiled against and run with, every blend the brew has
+    pub fn test_jars(&self) -> Vec<PathBuf> {
+        let mut jars = self.compile_jars();
+        jars.extend(self.jars_in(MavenDependencyScopeType::Test));
+        jars
+    }
+
+    /// The blend jars the brew is compiled against as a classpath, lib/* would also pick up the brew's own jar in a workspace
+    pub fn lib_class_path(&self) -> OsString {
+        env::join_paths(self.compile_jars()).expect("blend path can't be used in a classpath")
+    }
+
+    /// The jars of the blends in `scope` the brew was resolved to when it was last fetched, lib is shared by
+    /// every brew of a workspace and keeps the jars of blends that were removed or bumped
+    fn jars_in(&self, scope: MavenDependencyScopeType) -> Vec<PathBuf> {
+        let lib = crate::config::get_lib_path().unwrap();
+        let dir = scope.lib_dir(&lib);
+        let Ok(lock) = open_toml::<LockFile>(&get_lock_path()) else {
+            return vec![];
+        };
+        let mut jars = lock
+            .jars(self.brew().name())
+            .map(|jar| lib.join(jar))
+            .filter(|jar| jar.parent() == Some(dir.as_path()))
+            .collect::<Vec<_>>();
+        jars.sort();
+        jars
+    }
+}
+
+/// Collects the entries of a fat jar, deciding what happens when several jars ship the same file
+#[derive(Default)]
+struct FatEntries {
+    entries: BTreeMap<String, Vec<u8>>,
+    services: BTreeMap<String, Vec<String>>,
+}
+
+impl FatEntries {
+    fn add(&mut self, relocator: &Relocator, name: String, bytes: Vec<u8>) {
+        let upper = name.to_ascii_uppercase();
+        // manifests, signatures and module descriptors only describe the jar they came from
+        let signature = upper.strip_prefix("META-INF/").is_some_and(|file| {
+            !file.contains('/')
+                && [".SF", ".DSA", ".RSA", ".EC"]
+                    .iter()
+                    .any(|ext| file.ends_with(ext))
+        });
+        if signature || upper == "META-INF/MANIFEST.MF" || name.ends_with("module-info.class") {
+            return;
+        }
+        // every implementation of a service has to stay registered, so service files are merged
+        if let Some(service) = name
+            .strip_prefix("META-INF/services/")
+            .filter(|service| !service.is_empty())
+        {
+            let providers = self
+                .services
+                .entry(relocator.relocate_str(service))
+                .or_default();
+            for provider in String::from_utf8_lossy(&bytes).lines() {
+                let provider = relocator.relocate_str(provider.trim());
+                if !provider.is_empty() && !providers.contains(&provider) {
+                    providers.push(provider);
+                }
+            }
+            return;
+        }
+        let bytes = if name.ends_with(".class") && !relocator.is_empty() {
+            relocator
+                .relocate_class(&bytes)
+                .unwrap_or_else(|e| panic!("could not relocate {name}: {e}"))
+        } else {
+            bytes
+        };
+        // otherwise the first one wins, which keeps the first LICENSE
+        self.entries
+            .entry(relocator.relocate_str(&name))
+            .or_insert(bytes);
+    }
+
+    fn finish(mut self) -> BTreeMap<String, Vec<u8>> {
+        for (service, providers) in self.services {
+            self.entries.insert(
+                format!("META-INF/services/{service}"),
+                (providers.join("\n") + "\n").into_bytes(),
+            );
+        }
+        self.entries
+    }
+}
+
+/// A jar written by barista rather than the jdk's jar tool, entries are sorted and every
+/// timestamp and permission is fixed so the same classes always give a byte-identical jar
+pub struct JarBuilder {
+    manifest: String,
+    entries: BTreeMap<String, Vec<u8>>,
+}
+
+impl JarBuilder {
+    pub fn new(manifest: String) -> Self {
+        Self {
+            manifest,
+            entries: BTreeMap::new(),
+        }
+    }
+
+    pub fn insert(&mut self, name: String, bytes: Vec<u8>) {
+        self.entries.insert(name, bytes);
+    }
+
+    /// Adds every file under dir, named relative to it
+    pub fn add_dir(&mut self, dir: &Path) {
+        self.add_files(dir, walk_dir(dir));
+    }
+
+    /// Adds the given files from under dir, named relative to it
+    pub fn add_files(&mut self, dir: &Path, files: Vec<PathBuf>) {
+        for path in files {
+            let name = entry_name(path.strip_prefix(dir).unwrap());
+            let bytes = fs::read(&path)
+                .unwrap_or_else(|e| panic!("could not read {}: {e}", path.display()));
+            self.insert(name, bytes);
+        }
+    }
+
+    pub fn write(self, path: &Path, timestamp: DateTime) {
+        let mut jar = ZipWriter::new(
+            File::create(path)
+                .unwrap_or_else(|e| panic!("could not create {}: {e}", path.display())),
+        );
+        let options = FileOptions::default()
+            .compression_method(CompressionMethod::Deflated)
+            .last_modified_time(timestamp);
+        let file_options = options.unix_permissions(0o644);
+        let dir_options = options.unix_permissions(0o755);
+        // every parent directory gets its own entry, like the jar tool would make
+        let dirs = self
+            .entries
+            .keys()
+            .flat_map(|name| {
+                name.match_indices('/')
+                    .map(|(end, _)| name[..=end].to_string())
+                    .collect::<Vec<_>>()
+            })
+            .filter(|dir| dir != "META-INF/")
+            .collect::<BTreeSet<_>>();
+        // the manifest has to come first for JarInputStream to find it
+        jar.add_directory("META-INF/", dir_options).unwrap();
+        jar.start_file("META-INF/MANIFEST.MF", file_options)
             .unwrap();
+        jar.write_all(self.manifest.as_bytes()).unwrap();
+        for dir in dirs {
+            jar.add_directory(dir, dir_options).unwrap();
+        }
+        for (name, bytes) in self.entries {
+            jar.start_file(name, file_options).unwrap();
+            jar.write_all(&bytes).unwrap();
+        }
+        jar.finish()
+            .unwrap_or_else(|e| panic!("could not write {}: {e}", path.display()));
+    }
+}
+
+/// Converts seconds since the unix epoch to a zip timestamp (in UTC), zips can't go before 1980
+fn zip_time(epoch: i64) -> DateTime {
+    let (year, month, day, hour, minute, second) = civil_time(epoch);
+    if year < 1980 {
+        return DateTime::default();
+    }
+    DateTime::from_date_and_time(year.min(2107) as u16, month, day, hour, minute, second)
+        .unwrap_or_default()
+}
+
+/// Everything roast put in `bin`, leaving out the images link and package build there and the compiled tests
+fn roasted_files(bin: &Path) -> Vec<PathBuf> {
+    walk_dir(bin)
+        .into_iter()
+        .filter(|path| {
+            ![IMAGE_DIR, LIB_CLASSES_DIR, PACKAGE_DIR, TEST_DIR]
+                .iter()
+                .any(|dir| path.starts_with(bin.join(dir)))
+        })
+        .collect()
+}
+
+/// Jar entries always use `/`, no matter the platform
+fn entry_name(path: &Path) -> String {
+    path.components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+fn manifest(main_class: Option<&str>, class_path: &[String]) -> String {
+    let mut manifest = manifest_line("Manifest-Version", "1.0");
+    manifest += &manifest_line("Created-By", "barista");
+    if let Some(main_class) = main_class {
+        manifest += &manifest_line("Main-Class", main_class);
+    }
+    if !class_path.is_empty() {
+        manifest += &manifest_line("Class-Path", &class_path.join(" "));
+    }
+    manifest
+}
+
+/// Manifest lines can be at most 72 bytes long, longer values are continued on the next line after a single space
+fn manifest_line(key: &str, value: &str) -> String {
+    let line = format!("{key}: {value}");
+    let mut out = String::new();
+    let mut width = 0;
+    for c in line.chars() {
+        if width + c.len_utf8() > 72 {
+            out += "\r\n ";
+            width = 1;
+        }
+        out.push(c);
+        width += c.len_utf8();
+    }
+    out + "\r\n"
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ymd_hms(time: DateTime) -> (u16, u8, u8, u8, u8, u8) {
+        (
+            time.year(),
+            time.month(),
+            time.day(),
+            time.hour(),
+            time.minute(),
+            time.second(),
+        )
+    }
+
+    #[test]
+    fn zip_time_of_known_epoch() {
+        assert_eq!(ymd_hms(zip_time(1700000000)), (2023, 11, 14, 22, 13, 20));
+        assert_eq!(ymd_hms(zip_time(951782400)), (2000, 2, 29, 0, 0, 0));
+    }
+
+    #[test]
+    fn zip_time_before_1980_is_earliest_zip_time() {
+        let earliest = ymd_hms(DateTime::default());
+        assert_eq!(ymd_hms(zip_time(0)), earliest);
+        assert_eq!(ymd_hms(zip_time(315532799)), earliest);
+        assert_eq!(ymd_hms(zip_time(-86400)), earliest);
+        assert_eq!(ymd_hms(zip_time(315532800)), (1980, 1, 1, 0, 0, 0));
+    }
+
+    #[test]
+    fn zip_time_after_2107_is_clamped() {
+        assert_eq!(zip_time(5000000000).year(), 2107);
+    }
+
+    #[test]
+    fn jar_builder_writes_identical_jars() {
+        let dir = env::temp_dir().join(format!("barista-jar-test-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let write = |name: &str, entries: &[(&str, &[u8])]| {
+            let mut jar = JarBuilder::new("Manifest-Version: 1.0\r\n\r\n".to_string());
+            for (entry, bytes) in entries {
+                jar.insert(entry.to_string(), bytes.to_vec());
+            }
+            let path = dir.join(name);
+            jar.write(&path, zip_time(1700000000));
+            fs::read(path).unwrap()
+        };
+        let first = write(
+            "first.jar",
+            &[
+                ("a/b/C.class", b"cafe"),
+                ("Main.class", b"babe"),
+                ("a/D.txt", b"d"),
+            ],
+        );
+        let second = write(
+            "second.jar",
+            &[
+                ("a/D.txt", b"d"),
+                ("Main.class", b"babe"),
+                ("a/b/C.class", b"cafe"),
+            ],
+        );
+        fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(first, second);
     }
 }
@@ -0,0 +1,188 @@
+//! link.rs builds a trimmed down java runtime with just the modules a brew needs (using jdeps and jlink),
+//! together with the brew's jars and a launcher script
+use std::{
+    env, fs,
+    io::{self, ErrorKind},
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+};
+
+use crate::{
+    config::{BinNotFoundError, Config, OpenConfigError},
+    roast::{roast, RoastError, RoastOptions},
+    toolchain::{self, ToolchainError},
+};
+
+/// Where in `bin` the runtime image is built
+pub const IMAGE_DIR: &str = "image";
+
+#[derive(Debug, thiserror::Error)]
+pub enum LinkError {
+    #[error("{0}")]
+    Config(OpenConfigError),
+    #[error("{0}")]
+    Toolchain(ToolchainError),
+    #[error("{0}")]
+    Roast(RoastError),
+    #[error("{0}")]
+    Bin(BinNotFoundError),
+    #[error("error running {1}: {0}")]
+    Spawn(io::Error, String),
+    #[error("{0} failed")]
+    Failed(String),
+    #[error("error cleaning old image {1}: {0}")]
+    Clean(io::Error, String),
+    #[error("error copying {1} into the image: {0}")]
+    Copy(io::Error, String),
+    #[error("error writing launcher {1}: {0}")]
+    Launcher(io::Error, String),
+}
+
+#[derive(clap::Args, Clone, Debug)]
+pub struct LinkOptions {
+    /// strip debug information from the runtime image
+    #[clap(long)]
+    strip_debug: bool,
+    /// compression level passed on to jlink (ie 2 or zip-6 depending on the jdk)
+    #[clap(long)]
+    compress: Option<String>,
+    /// modules to add on top of the ones jdeps finds (comma separated)
+    #[clap(long, value_delimiter = ',')]
+    add_modules: Vec<String>,
+}
+
+pub fn link(bin: Option<String>, options: LinkOptions) -> Result<(), LinkError> {
+    roast(bin.clone(), RoastOptions::default()).map_err(LinkError::Roast)?;
+    let config = Config::find_and_open_config().map_err(LinkError::Config)?;
+    config.bin_jar(bin.clone()).map_err(LinkError::Bin)?;
+    let root = crate::config::get_root_path().unwrap();
+    let main_class = config
+        .main_class(bin, &config.source_root(&root))
+        .map_err(LinkError::Bin)?;
+
+    let jdk = toolchain::find(&config).map_err(LinkError::Toolchain)?;
+    let jdeps_bin = jdk.tool("jdeps");
+    let jlink_bin = jdk.jlink();
+
+    // an old image in bin would otherwise be scanned by jdeps
+    let image = root.join("bin").join(IMAGE_DIR);
+    if let Err(e) = fs::remove_dir_all(&image) {
+        if e.kind() != ErrorKind::NotFound {
+            return Err(LinkError::Clean(e, image.display().to_string()));
+        }
+    }
+    let lib_jars = config.lib_jars();
+    let mut modules = jdeps(jdeps_bin, &root, &lib_jars, jdk.release().feature())?;
+    modules.extend(options.add_modules);
+    modules.sort();
+    modules.dedup();
+    println!("linking modules {}", modules.join(","));
+
+    let mut jlink = Command::new(&jlink_bin);
+    jlink
+        .arg("--add-modules")
+        .arg(modules.join(","))
+        .arg("--output")
+        .arg(&image)
+        .arg("--no-header-files")
+        .arg("--no-man-pages");
+    if options.strip_debug {
+        jlink.arg("--strip-debug");
+    }
+    if let Some(compress) = options.compress {
+        jlink.arg(format!("--compress={compress}"));
+    }
+    run(&mut jlink, "jlink")?;
+
+    // the brew's jars get their own directory, the image's lib belongs to the runtime
+    copy_into(&config.artifact_path(None), &image.join("app"))?;
+    for jar in &lib_jars {
+        copy_into(jar, &image.join("app"))?;
+    }
+    write_launcher(&image, config.brew().name(), &main_class)?;
+    println!("runtime image created in {}", image.display());
+    Ok(())
+}
+
+/// Asks jdeps which jdk modules the brew's classes and blends use
+pub fn jdeps(
+    jdeps_bin: PathBuf,
+    root: &Path,
+    lib_jars: &[PathBuf],
+    release: u32,
+) -> Result<Vec<String>, LinkError> {
+    let mut command = Command::new(jdeps_bin);
+    command
+        .arg("--print-module-deps")
+        .arg("--ignore-missing-deps")
+        .arg("--multi-release")
+        .arg(release.to_string())
+        .arg("-q");
+    // jdeps rejects an empty --class-path, which a brew without blends would give it
+    if !lib_jars.is_empty() {
+        command
+            .arg("--class-path")
+            .arg(env::join_paths(lib_jars).expect("blend path can't be used in a classpath"));
+    }
+    let output = command
+        .arg(root.join("bin"))
+        .args(lib_jars)
+        .stderr(Stdio::inherit())
+        .output()
+        .map_err(|error| LinkError::Spawn(error, "jdeps".to_string()))?;
+    if !output.status.success() {
+        return Err(LinkError::Failed("jdeps".to_string()));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .trim()
+        .split(',')
+        .filter(|module| !module.is_empty())
+        .map(str::to_string)
+        .collect())
+}
+
+fn run(command: &mut Command, name: &str) -> Result<(), LinkError> {
+    let status = command
+        .stdout(Stdio::inherit())
+        .stderr(Stdio::inherit())
+        .status()
+        .map_err(|error| LinkError::Spawn(error, name.to_string()))?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(LinkError::Failed(name.to_string()))
+    }
+}
+
+fn copy_into(file: &Path, dir: &Path) -> Result<(), LinkError> {
+    fs::create_dir_all(dir).map_err(|error| LinkError::Copy(error, dir.display().to_string()))?;
+    fs::copy(file, dir.join(file.file_name().unwrap()))
+        .map_err(|error| LinkError::Copy(error, file.display().to_string()))?;
+    Ok(())
+}
+
+#[cfg(not(target_os = "windows"))]
+fn write_launcher(image: &Path, name: &str, main_class: &str) -> Result<(), LinkError> {
+    use std::os::unix::fs::PermissionsExt;
+    let launcher = image.join("bin").join(name);
+    fs::write(
+        &launcher,
+        format!(
+            "#!/bin/sh\nDIR=\"$(cd \"$(dirname \"$0\")\" && pwd)\"\nexec \"$DIR/java\" $JAVA_OPTS -cp \"$DIR/../app/*\" {main_class} \"$@\"\n"
+        ),
+    )
+    .and_then(|()| fs::set_permissions(&launcher, fs::Permissions::from_mode(0o755)))
+    .map_err(|error| LinkError::Launcher(error, launcher.display().to_string()))
+}
+
+#[cfg(target_os = "windows")]
+fn write_launcher(image: &Path, name: &str, main_class: &str) -> Result<(), LinkError> {
+    let launcher = image.join("bin").join(format!("{name}.bat"));
+    fs::write(
+        &launcher,
+        format!(
+            "@echo off\r\n\"%~dp0java.exe\" %JAVA_OPTS% -cp \"%~dp0..\\app\\*\" {main_class} %*\r\n"
+        ),
+    )
+    .map_err(|error| LinkError::Launcher(error, launcher.display().to_string()))
+}
@@ -1,27 +1,83 @@
+use std::collections::{BTreeMap, BTreeSet};
+
 use semver::Version;
-use serde::Serialize;
-#[derive(Serialize, Debug)]
+use serde::{Deserialize, Serialize};
+#[derive(Serialize, Deserialize, Debug)]
 pub struct LockFile {
-    name: String,
-    version: Version,
+    /// the brew the lock file belongs to, not set for a workspace's lock file
+    #[serde(skip_serializing_if = "Option::is_none")]
+    name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    version: Option<Version>,
+    /// the features each brew (or workspace member) was built with
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    features: BTreeMap<String, Vec<String>>,
+    /// the blend jars each brew (or workspace member) was resolved to, relative to lib
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    jars: BTreeMap<String, BTreeSet<String>>,
     brews: Vec<Package>,
 }
 
 impl LockFile {
     pub fn new(name: String, version: Version) -> Self {
         Self {
-            name,
-            version,
+            name: Some(name),
+            version: Some(version),
+            features: BTreeMap::new(),
+            jars: BTreeMap::new(),
             brews: vec![],
         }
     }
 
+    /// The lock file shared by every member of a workspace
+    pub fn workspace() -> Self {
+        Self {
+            name: None,
+            version: None,
+            features: BTreeMap::new(),
+            jars: BTreeMap::new(),
+            brews: vec![],
+        }
+    }
+
+    pub fn set_features(&mut self, brew: String, features: Vec<String>) {
+        if !features.is_empty() {
+            self.features.insert(brew, features);
+        }
+    }
+
+    /// Records that `brew` uses the blend jar at `jar` (relative to lib)
+    pub fn add_jar(&mut self, brew: &str, jar: String) {
+        self.jars.entry(brew.to_string()).or_default().insert(jar);
+    }
+
+    /// The blend jars `brew` was resolved to, relative to lib
+    pub fn jars(&self, brew: &str) -> impl Iterator<Item = &str> {
+        self.jars
+            .get(brew)
+            .into_iter()
+            .flatten()
+            .map(String::as_str)
+    }
+
     pub fn push(&mut self, value: Package) {
         self.brews.push(value)
     }
-}
-#[derive(Serialize, Debug)]
 
+    pub fn find(&self, name: &str, authors: &str) -> Option<&Package> {
+        self.brews
+            .iter()
+            .find(|brew| brew.name == name && brew.authors == authors)
+    }
+
+    /// Finds a path or git blend, which unlike maven blends are only known by name in Brew.toml
+    pub fn find_local(&self, name: &str) -> Option<&Package> {
+        self.brews
+            .iter()
+            .find(|brew| brew.name == name && brew.source.is_some())
+    }
+}
+#[derive(Serialize, Deserialize, Debug)]
 pub struct Package {
     name: String,
     // TODO: make this be a Version type
@@ -30,6 +86,9 @@ pub struct Package {
     url: String,
     source: Option<String>,
     dependencies: Option<Vec<String>>,
+    /// the features a path or git blend was built with
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    features: Vec<String>,
 }
 
 impl Package {
@@ -48,9 +107,26 @@ impl Package {
             url,
             source,
             dependencies,
+            features: vec![],
         }
     }
 
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn version(&self) -> &str {
+        &self.version
+    }
+
+    pub fn authors(&self) -> &str {
+        &self.authors
+    }
+
+    pub fn set_features(&mut self, features: Vec<String>) {
+        self.features = features;
+    }
+
     pub fn set_dependencies(&mut self, dependencies: Vec<String>) {
         self.dependencies = Some(dependencies);
     }
@@ -1,26 +1,45 @@
 // main.rs is where the argument parsing is done using [clap](https://crates.io/crates/clap)
 // TODO: theres a lot of Error type duplication and sometimes Error types can be more specific
 // TODO: check if file/dir is already there and in most cases if so do nothing
-use std::process::exit;
+use std::{env, process::exit};
 
-use crate::roast::roast;
-use crate::{brew::brew, mix::add_dependency};
-use clap::{arg, Parser, Subcommand};
-use config::BlendConfig;
-use craft::create_new_brew;
+use crate::roast::{roast, RoastOptions};
+use crate::{
+    brew::{brew, DebugOptions},
+    mix::add_dependency,
+};
+use clap::{Parser, Subcommand};
+use config::{BinNotFoundError, BlendConfig, Config, FeatureOptions};
+use craft::{create_new_brew, init_brew};
+use diagnostics::MessageFormat;
+use link::LinkOptions;
 use menu::make_menu;
 use semver::VersionReq;
+use watch::WatchCommand;
 
 mod brew;
 mod config;
 mod craft;
+mod daemon;
+mod diagnostics;
 mod fetch;
+mod import;
 mod jar;
+mod link;
 mod lock;
 mod menu;
 mod mix;
+mod module;
+mod package;
+mod publish;
+mod resources;
 mod roast;
+mod shade;
+mod template;
+mod toolchain;
 mod utils;
+mod watch;
+mod workspace;
 pub mod sip;
 
 #[derive(Parser, Debug)]
@@ -28,29 +47,126 @@ pub mod sip;
 struct Args {
     #[clap(subcommand)]
     command: CommandType,
+    /// the bin target to build, run or package, Main.java if not given
     #[clap(long)]
     bin: Option<String>,
+    /// how compiler and javadoc errors and warnings are printed
+    #[clap(long, value_enum, global = true, default_value_t)]
+    message_format: MessageFormat,
+    /// run for every member of the workspace
+    #[clap(long, global = true)]
+    workspace: bool,
+    /// run for the given workspace member (can be given more than once)
+    #[clap(short = 'p', long = "package", value_name = "member", global = true)]
+    package: Vec<String>,
 }
 #[derive(Subcommand, Clone, Debug)]
 pub enum CommandType {
     /// Build and run the current [Brew] (package)
-    Brew,
+    Brew {
+        /// the bin target to run, Main.java if not given
+        #[clap(long)]
+        bin: Option<String>,
+        #[clap(flatten)]
+        debug: DebugOptions,
+        /// arguments passed on to the program, after --
+        #[clap(last = true)]
+        args: Vec<String>,
+    },
     /// Build the current [Brew] (package)
     Roast {
+        /// the bin target to build, Main.java if not given
+        #[clap(long)]
+        bin: Option<String>,
         /// compile to jar
         #[clap(long)]
         jar: bool,
+        #[clap(flatten)]
+        features: FeatureOptions,
+    },
+    /// Package the current [Brew] (package) into a runnable jar
+    Jar {
+        /// the bin target the jar runs, Main.java if not given
+        #[clap(long)]
+        bin: Option<String>,
+        /// include all blends (dependencies) in the jar
+        #[clap(long)]
+        fat: bool,
+        /// also package the sources into a -sources.jar
+        #[clap(long)]
+        sources: bool,
+        /// also document the brew and package it into a -javadoc.jar
+        #[clap(long)]
+        javadoc: bool,
     },
     /// Create a new [Brew] (package) with the given name
-    Craft { name: String },
+    Craft {
+        name: String,
+        /// start from the library template, with a test
+        #[clap(long, conflicts_with_all(["bin", "template"]))]
+        lib: bool,
+        /// start from the command line app template (the default)
+        #[clap(long, conflicts_with_all(["lib", "template"]))]
+        bin: bool,
+        /// start from a template in ~/.barista/templates, a directory or a git repository
+        #[clap(long, value_name = "name|path|git-url", conflicts_with_all(["lib", "bin"]))]
+        template: Option<String>,
+    },
+    /// Make an existing java project a [Brew], importing its pom.xml or build.gradle if it has one
+    Init {
+        /// the project's directory
+        #[clap(default_value = ".")]
+        path: String,
+    },
     /// Add a new [Blend] (dependency) to the current brew
     Mix(Blend),
     /// Document the current [Blend]
     Menu,
+    /// Build a custom java runtime image for the current [Brew] (package) with jlink
+    Link {
+        /// the bin target the runtime image launches, Main.java if not given
+        #[clap(long)]
+        bin: Option<String>,
+        #[clap(flatten)]
+        options: LinkOptions,
+    },
+    /// Build native packages (an app image, .deb, .rpm ...) of the current [Brew] (package) with jpackage
+    Package {
+        /// the bin target the packages launch, Main.java if not given
+        #[clap(long)]
+        bin: Option<String>,
+        /// package types to build, overrides [package] types in Brew.toml
+        #[clap(long = "type", value_delimiter = ',')]
+        types: Vec<String>,
+    },
+    /// Publish the current [Brew] (package) to a Maven repository
+    Publish {
+        /// repository to publish to, overrides [publish] repository in Brew.toml
+        #[clap(long)]
+        repository: Option<String>,
+        /// show what would be uploaded without uploading it
+        #[clap(long)]
+        dry_run: bool,
+    },
+    /// Rerun a command whenever the sources, resources or Brew.toml of the current [Brew] (package) change
+    Watch {
+        /// the bin target to build or run on every change, Main.java if not given
+        #[clap(long)]
+        bin: Option<String>,
+        /// what to run on every change
+        #[clap(value_enum, default_value_t)]
+        command: WatchCommand,
+        /// arguments passed on to the program when watching brew, after --
+        #[clap(last = true)]
+        args: Vec<String>,
+    },
     /// Run Tests
     Sip {
-        filter: Option<String>
-    }
+        /// only run test classes whose name contains this
+        filter: Option<String>,
+        #[clap(flatten)]
+        debug: DebugOptions,
+    },
 }
 #[derive(clap::Parser, Clone, Debug)]
 #[clap(args_conflicts_with_subcommands = true)]
@@ -95,22 +211,196 @@ impl From<Blend> for BlendConfig {
 
 fn main() {
     let args = Args::parse();
-    match args.command {
-        CommandType::Brew {} => brew(args.bin),
-        CommandType::Roast {jar} => roast(args.bin),
-        CommandType::Craft { name } => {
-            if let Err(e) = create_new_brew(&name) {
-                println!("Error creating new Brew\n{e}");
+    // a new brew is crafted where barista is run, whatever workspace that is in
+    let members = if matches!(
+        args.command,
+        CommandType::Craft { .. } | CommandType::Init { .. }
+    ) {
+        Ok(None)
+    } else {
+        workspace::select_members(args.workspace, &args.package)
+    };
+    match members {
+        Ok(None) => run(args.command, args.bin, args.message_format),
+        Ok(Some(members)) => {
+            if members.len() > 1
+                && matches!(
+                    args.command,
+                    CommandType::Brew { .. } | CommandType::Watch { .. }
+                )
+            {
+                eprintln!("Error selecting workspace members\nthis command runs a single brew, pick one with -p");
+                exit(1);
+            }
+            // every command works on the brew it is run in
+            for member in members {
+                if let Err(e) = env::set_current_dir(&member) {
+                    eprintln!("Error entering workspace member {}\n{e}", member.display());
+                    exit(1);
+                }
+                if args.message_format == MessageFormat::Human {
+                    println!("{}", member.display());
+                }
+                run(args.command.clone(), args.bin.clone(), args.message_format);
+            }
+        }
+        Err(e) => {
+            eprintln!("Error selecting workspace members\n{e}");
+            exit(1);
+        }
+    }
+}
+
+fn run(command: CommandType, bin: Option<String>, message_format: MessageFormat) {
+    match command {
+        CommandType::Brew {
+            bin: own_bin,
+            debug,
+            args: program_args,
+        } => exit(brew(own_bin.or(bin), program_args, debug, message_format)),
+        CommandType::Roast {
+            bin: own_bin,
+            jar,
+            features,
+        } => {
+            let bin = own_bin.or(bin);
+            roast_or_exit(bin.clone(), message_format, features);
+            if jar {
+                jar_or_exit(config_or_exit().bin_jar(bin));
+            }
+        }
+        CommandType::Jar {
+            bin: own_bin,
+            fat,
+            sources,
+            javadoc,
+        } => {
+            let bin = own_bin.or(bin);
+            roast_or_exit(bin.clone(), message_format, FeatureOptions::default());
+            let config = config_or_exit();
+            jar_or_exit(if fat {
+                config.fat_jar(bin)
+            } else {
+                config.bin_jar(bin)
+            });
+            if sources {
+                config.sources_jar();
+            }
+            if javadoc {
+                menu_or_exit(message_format);
+                config.javadoc_jar();
+            }
+        }
+        CommandType::Craft {
+            name,
+            lib,
+            bin: _,
+            template,
+        } => {
+            let template = match template {
+                Some(template) => template,
+                None if lib => "lib".to_string(),
+                None => template::DEFAULT_TEMPLATE.to_string(),
+            };
+            if let Err(e) = create_new_brew(&name, &template) {
+                eprintln!("Error creating new Brew\n{e}");
+                exit(1);
+            }
+        }
+        CommandType::Init { path } => {
+            if let Err(e) = init_brew(&path) {
+                eprintln!("Error initializing Brew\n{e}");
                 exit(1);
             }
         }
         CommandType::Mix(blend) => {
             if let Err(e) = add_dependency(&blend.name.clone(), blend.clone().into()) {
-                println!("Error adding dependency {blend:?}\n{e:?}");
+                eprintln!("Error adding dependency {blend:?}\n{e:?}");
+                exit(1);
+            }
+        }
+        CommandType::Menu => menu_or_exit(message_format),
+        CommandType::Link {
+            bin: own_bin,
+            options,
+        } => {
+            if let Err(e) = link::link(own_bin.or(bin), options) {
+                eprintln!("Error linking Brew\n{e}");
+                exit(1);
+            }
+        }
+        CommandType::Package {
+            bin: own_bin,
+            types,
+        } => {
+            if let Err(e) = package::package(own_bin.or(bin), types) {
+                eprintln!("Error packaging Brew\n{e}");
                 exit(1);
             }
         }
-        CommandType::Menu => make_menu(),
-        CommandType::Sip { filter } => sip::sip(filter),
+        CommandType::Publish {
+            repository,
+            dry_run,
+        } => {
+            if let Err(e) = publish::publish(repository, dry_run) {
+                eprintln!("Error publishing Brew\n{e}");
+                exit(1);
+            }
+        }
+        CommandType::Watch {
+            bin: own_bin,
+            command,
+            args: program_args,
+        } => {
+            if let Err(e) = watch::watch(own_bin.or(bin), command, program_args) {
+                eprintln!("Error watching Brew\n{e}");
+                exit(1);
+            }
+        }
+        CommandType::Sip { filter, debug } => {
+            match sip::sip(filter, debug, false, message_format) {
+                Ok(0) => {}
+                Ok(code) => exit(code),
+                Err(e) => {
+                    eprintln!("Error running tests\n{e}");
+                    exit(1);
+                }
+            }
+        }
+    }
+}
+
+fn roast_or_exit(bin: Option<String>, message_format: MessageFormat, features: FeatureOptions) {
+    if let Err(e) = roast(
+        bin,
+        RoastOptions {
+            message_format,
+            features,
+            ..Default::default()
+        },
+    ) {
+        eprintln!("Error roasting Brew\n{e}");
+        exit(1);
+    }
+}
+
+fn config_or_exit() -> Config {
+    Config::find_and_open_config().unwrap_or_else(|e| {
+        eprintln!("Error opening Brew.toml\n{e}");
+        exit(1);
+    })
+}
+
+fn jar_or_exit(result: Result<(), BinNotFoundError>) {
+    if let Err(e) = result {
+        eprintln!("Error packaging Brew into a jar\n{e}");
+        exit(1);
+    }
+}
+
+fn menu_or_exit(message_format: MessageFormat) {
+    if let Err(e) = make_menu(message_format) {
+        eprintln!("Error documenting Brew\n{e}");
+        exit(1);
     }
 }
@@ -1,51 +1,76 @@
 use std::{
-    fs,
-    io::ErrorKind,
-    path::PathBuf,
-    process::{Command, Stdio},
+    env, fs,
+    io::{self, ErrorKind},
+    process::Command,
 };
 
-use javaup::config;
+use crate::{
+    config::{Config, OpenConfigError},
+    diagnostics::{self, MessageFormat},
+    fetch::FetchError,
+    toolchain::{self, ToolchainError},
+    utils::walk_dir,
+};
 
-use crate::config::Config;
+#[derive(Debug, thiserror::Error)]
+pub enum MenuError {
+    #[error("{0}")]
+    Config(OpenConfigError),
+    #[error("{0}")]
+    Toolchain(ToolchainError),
+    #[error("{0}")]
+    Fetch(FetchError),
+    #[error("error running javadoc: {0}")]
+    Spawn(io::Error),
+    #[error("could not document the brew due to {0} error(s)")]
+    Document(usize),
+}
 
-pub fn make_menu() {
-    Config::find_and_open_config().unwrap().fetch();
+pub fn make_menu(message_format: MessageFormat) -> Result<(), MenuError> {
+    let config = Config::find_and_open_config().map_err(MenuError::Config)?;
+    config.fetch().map_err(MenuError::Fetch)?;
+    let jdk = toolchain::find(&config).map_err(MenuError::Toolchain)?;
     let binding = crate::config::get_root_path().unwrap();
     let root = binding.display();
-    let java_config = config::config_file();
-    let mut java_bin = config::jdkdir();
-    // create docs dir if doest exist
-    let create_dir = fs::create_dir(format!("{}/doc", root));
-    if create_dir
-        .as_ref()
-        .is_err_and(|e| e.kind() != ErrorKind::AlreadyExists)
-    {
-        panic!("error creating doc dir: {}", create_dir.unwrap_err())
+    // start from an empty doc dir so pages of deleted classes don't end up in the javadoc jar
+    if let Err(e) = fs::remove_dir_all(format!("{root}/doc")) {
+        if e.kind() != ErrorKind::NotFound {
+            panic!("error cleaning doc dir: {e}")
+        }
     }
-    java_bin.push(java_config.default_jdk.clone().unwrap().distribution);
-    java_bin.push(java_config.default_jdk.unwrap().version);
-    java_bin.push("bin");
-    #[cfg(target_os = "windows")]
-    java_bin.push("javadoc.exe");
-    #[cfg(not(target_os = "windows"))]
-    java_bin.push("javadoc");
-
-    // TODO: make this not hard coded also allow multiple all parts to be deocmneted
-    let bin_path =
-        PathBuf::from_iter(([root.to_string(), "src".to_string(), "Main".to_string(),  "Main.java".to_string()]));
-    let bin_path = bin_path.display();
-    let mut binding = Command::new(java_bin);
-    let javac_ex = binding
+    if let Err(e) = fs::create_dir(format!("{root}/doc")) {
+        panic!("error creating doc dir: {e}")
+    }
+    let source_roots = config.source_roots(&binding, config.brew().source());
+    // the tests aren't part of the brew's api
+    let test_root = config.test_root(&binding);
+    let sources = source_roots
+        .iter()
+        .flat_map(|source_root| walk_dir(source_root))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "java"))
+        .filter(|path| !path.starts_with(&test_root));
+    let output = Command::new(jdk.javadoc())
+        // progress messages would end up between the diagnostics
+        .arg("-quiet")
+        .args(toolchain::release_args(&jdk))
         .arg("-d")
         .arg(format!("{root}/doc"))
+        .arg("-cp")
+        .arg(config.lib_class_path())
         .arg("--source-path")
-        .arg(format!("{root}/src/"))
-        .arg(format!("{bin_path}"))
-        // ignoring output untill we have good way to filter/present it
-        // .stdout(Stdio::null())
-        // .stderr(Stdio::null())
-        ;
-
-    javac_ex.status().unwrap();
+        .arg(env::join_paths(&source_roots).expect("brew path can't be used in a source path"))
+        .args(sources)
+        .output()
+        .map_err(MenuError::Spawn)?;
+    let errors = diagnostics::report(
+        "javadoc",
+        &diagnostics::tool_output(&output),
+        message_format,
+        &binding,
+    );
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(MenuError::Document(errors))
+    }
 }
@@ -0,0 +1,86 @@
+//! module.rs has the Java Platform Module System support, brews with a `src/module-info.java` are
+//! compiled and run on the module path instead of the classpath
+use std::{
+    env,
+    ffi::OsString,
+    fs::{self, File},
+    io::Read,
+    path::{Path, PathBuf},
+};
+
+use zip::ZipArchive;
+
+use crate::{config::Config, utils::strip_comments};
+
+/// The blends of a modular brew, split by where java has to look for them
+#[derive(Debug, Default)]
+pub struct ModulePaths {
+    /// jars that are (automatic) modules
+    pub module_path: Vec<PathBuf>,
+    /// plain jars, which only the unnamed module can see
+    pub class_path: Vec<PathBuf>,
+}
+
+impl ModulePaths {
+    pub fn module_path(&self) -> OsString {
+        join(&self.module_path)
+    }
+
+    pub fn class_path(&self) -> OsString {
+        join(&self.class_path)
+    }
+}
+
+impl Config {
+    /// Splits the blends the brew is compiled against into the module path and the classpath
+    pub fn module_paths(&self) -> ModulePaths {
+        let (module_path, class_path) = self
+            .compile_jars()
+            .into_iter()
+            .partition(|jar| is_module(jar));
+        ModulePaths {
+            module_path,
+            class_path,
+        }
+    }
+}
+
+/// The name declared in `module-info.java` in the source root, if the brew is a module
+pub fn module_name(source_root: &Path) -> Option<String> {
+    let module_info = fs::read_to_string(source_root.join("module-info.java")).ok()?;
+    let module_info = strip_comments(&module_info);
+    let mut tokens = module_info
+        .split(|c: char| c.is_whitespace() || c == '{')
+        .filter(|token| !token.is_empty());
+    // annotations and `open` can come before `module`
+    tokens.find(|token| *token == "module")?;
+    tokens.next().map(str::to_string)
+}
+
+/// A jar is a module if it has a module descriptor or names itself with `Automatic-Module-Name`
+pub fn is_module(jar: &Path) -> bool {
+    let Ok(mut archive) = File::open(jar)
+        .map_err(zip::result::ZipError::Io)
+        .and_then(ZipArchive::new)
+    else {
+        return false;
+    };
+    let descriptor = archive.file_names().any(|name| {
+        name == "module-info.class"
+            || name.starts_with("META-INF/versions/") && name.ends_with("/module-info.class")
+    });
+    if descriptor {
+        return true;
+    }
+    let mut manifest = String::new();
+    archive
+        .by_name("META-INF/MANIFEST.MF")
+        .is_ok_and(|mut file| file.read_to_string(&mut manifest).is_ok())
+        && manifest
+            .lines()
+            .any(|line| line.starts_with("Automatic-Module-Name:"))
+}
+
+fn join(paths: &[PathBuf]) -> OsString {
+    env::join_paths(paths).expect("blend path can't be used in a module path")
+}
@@ -0,0 +1,174 @@
+//! package.rs builds native packages of a brew with jpackage, an app image everywhere and
+//! installers such as .deb and .rpm where the machine has the tools for them
+use std::{
+    env, fs,
+    io::{self, ErrorKind},
+    path::Path,
+    process::{Command, Stdio},
+};
+
+use crate::{
+    config::{BinNotFoundError, Config, OpenConfigError, PackageConfig},
+    link::{jdeps, LinkError},
+    roast::{roast, RoastError, RoastOptions},
+    toolchain::{self, ToolchainError},
+};
+
+/// Where in `bin` the packages are built
+pub const PACKAGE_DIR: &str = "package";
+
+#[derive(Debug, thiserror::Error)]
+pub enum PackageError {
+    #[error("{0}")]
+    Config(OpenConfigError),
+    #[error("{0}")]
+    Toolchain(ToolchainError),
+    #[error("{0}")]
+    Roast(RoastError),
+    #[error("{0}")]
+    Bin(BinNotFoundError),
+    #[error("{0}")]
+    Jdeps(Box<LinkError>),
+    #[error("error running jpackage: {0}")]
+    Spawn(io::Error),
+    #[error("jpackage failed to build {0}")]
+    Failed(String),
+    #[error("error cleaning old packages {1}: {0}")]
+    Clean(io::Error, String),
+    #[error("error copying {1} into the package input: {0}")]
+    Copy(io::Error, String),
+}
+
+pub fn package(bin: Option<String>, types: Vec<String>) -> Result<(), PackageError> {
+    roast(bin.clone(), RoastOptions::default()).map_err(PackageError::Roast)?;
+    let config = Config::find_and_open_config().map_err(PackageError::Config)?;
+    config.bin_jar(bin.clone()).map_err(PackageError::Bin)?;
+    let root = crate::config::get_root_path().unwrap();
+    let main_class = config
+        .main_class(bin, &config.source_root(&root))
+        .map_err(PackageError::Bin)?;
+    let default_package = PackageConfig::default();
+    let package_config = config.package().unwrap_or(&default_package);
+    let types = if types.is_empty() {
+        package_config
+            .types()
+            .cloned()
+            .unwrap_or_else(default_types)
+    } else {
+        types
+    };
+
+    let jdk = toolchain::find(&config).map_err(PackageError::Toolchain)?;
+    let jdeps_bin = jdk.tool("jdeps");
+    let jpackage_bin = jdk.tool("jpackage");
+
+    // jpackage refuses to overwrite an existing app image
+    let out = root.join("bin").join(PACKAGE_DIR);
+    if let Err(e) = fs::remove_dir_all(&out) {
+        if e.kind() != ErrorKind::NotFound {
+            return Err(PackageError::Clean(e, out.display().to_string()));
+        }
+    }
+    let lib_jars = config.lib_jars();
+    let modules = jdeps(jdeps_bin, &root, &lib_jars, jdk.release().feature())
+        .map_err(|e| PackageError::Jdeps(Box::new(e)))?;
+
+    // jpackage puts every jar of its input on the classpath of the launcher
+    let input = out.join("input");
+    let app_jar = config.artifact_path(None);
+    for jar in std::iter::once(&app_jar).chain(&lib_jars) {
+        fs::create_dir_all(&input)
+            .and_then(|()| fs::copy(jar, input.join(jar.file_name().unwrap())))
+            .map_err(|error| PackageError::Copy(error, jar.display().to_string()))?;
+    }
+
+    let brew = config.brew();
+    for package_type in types {
+        let mut jpackage = Command::new(&jpackage_bin);
+        jpackage
+            .arg("--type")
+            .arg(&package_type)
+            .arg("--name")
+            .arg(brew.name())
+            // installers only take plain numeric versions
+            .arg("--app-version")
+            .arg(format!(
+                "{}.{}.{}",
+                brew.version().major,
+                brew.version().minor,
+                brew.version().patch
+            ))
+            .arg("--vendor")
+            .arg(package_config.vendor().map_or(brew.group(), String::as_str))
+            .arg("--input")
+            .arg(&input)
+            .arg("--main-jar")
+            .arg(app_jar.file_name().unwrap())
+            .arg("--main-class")
+            .arg(&main_class)
+            .arg("--dest")
+            .arg(&out);
+        if !modules.is_empty() {
+            jpackage.arg("--add-modules").arg(modules.join(","));
+        }
+        if let Some(description) = brew.description() {
+            jpackage.arg("--description").arg(description);
+        }
+        if let Some(copyright) = package_config.copyright() {
+            jpackage.arg("--copyright").arg(copyright);
+        }
+        if let Some(icon) = package_config.icon() {
+            jpackage.arg("--icon").arg(root.join(icon));
+        }
+        for option in package_config.java_options() {
+            jpackage.arg("--java-options").arg(option);
+        }
+        // app images don't get installed so jpackage rejects the installer options for them,
+        // and the linux ones on other platforms
+        if package_type != "app-image" {
+            if let Some(repository) = brew.repository() {
+                jpackage.arg("--about-url").arg(repository);
+            }
+            if cfg!(target_os = "linux") {
+                if let Some(category) = package_config.linux_category() {
+                    jpackage.arg("--linux-app-category").arg(category);
+                }
+                if package_config.shortcut() {
+                    jpackage.arg("--linux-shortcut");
+                }
+            }
+        }
+        println!("packaging {package_type}");
+        let status = jpackage
+            .stdout(Stdio::inherit())
+            .stderr(Stdio::inherit())
+            .status()
+            .map_err(PackageError::Spawn)?;
+        if !status.success() {
+            return Err(PackageError::Failed(package_type));
+        }
+    }
+    if let Err(e) = fs::remove_dir_all(&input) {
+        return Err(PackageError::Clean(e, input.display().to_string()));
+    }
+    println!("packages created in {}", out.display());
+    Ok(())
+}
+
+/// An app image, plus a .deb and a .rpm on linux if dpkg-deb and rpmbuild are installed
+fn default_types() -> Vec<String> {
+    let mut types = vec!["app-image".to_string()];
+    if cfg!(target_os = "linux") {
+        for (package_type, tool) in [("deb", "dpkg-deb"), ("rpm", "rpmbuild")] {
+            if on_path(tool) {
+                types.push(package_type.to_string());
+            }
+        }
+    }
+    types
+}
+
+fn on_path(tool: &str) -> bool {
+    env::var_os("PATH")
+        .is_some_and(|path| env::split_paths(&path).any(|dir| Path::new(&dir).join(tool).is_file()))
+}
@@ -0,0 +1,548 @@
+//! publish.rs packages a brew with a generated pom.xml and uploads it to a maven repository
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use md5::Md5;
+use reqwest::{Client, RequestBuilder, StatusCode};
+use serde::{Deserialize, Serialize};
+use sha1::{Digest, Sha1};
+use sha2::Sha256;
+use tokio::runtime::{Builder, Runtime};
+
+use crate::{
+    config::{Config, OpenConfigError, PublishConfig},
+    diagnostics::MessageFormat,
+    fetch::{get_lock_path, to_version, FetchError, MavenDependencyScopeType},
+    lock::LockFile,
+    menu::{make_menu, MenuError},
+    utils::{civil_time, open_toml, TomlOpenError},
+};
+
+#[derive(Debug, thiserror::Error)]
+pub enum PublishError {
+    #[error("{0}")]
+    Config(OpenConfigError),
+    #[error("{0}")]
+    Fetch(FetchError),
+    #[error("{0}")]
+    Menu(MenuError),
+    #[error("no repository to publish to, set repository under [publish] in Brew.toml or pass --repository")]
+    NoRepository,
+    #[error("error generating pom.xml: {0}")]
+    Pom(quick_xml::DeError),
+    #[error("error reading or generating maven-metadata.xml: {0}")]
+    Metadata(quick_xml::DeError),
+    #[error("blend {0} has no locked version, run barista fetch first")]
+    Unlocked(String),
+    #[error("could not open path blend {0}: {1}")]
+    PathBlend(String, Box<TomlOpenError>),
+    #[error("error writing file {1}: {0}")]
+    FileWrite(io::Error, String),
+    #[error("error reading file {1}: {0}")]
+    FileRead(io::Error, String),
+    #[error("error copying to {1}: {0}")]
+    Copy(io::Error, String),
+    #[error("error uploading to {1}: {0}")]
+    Upload(reqwest::Error, String),
+    #[error("{1} rejected the upload with {0}")]
+    Rejected(StatusCode, String),
+    #[error("error downloading {1}: {0}")]
+    Download(reqwest::Error, String),
+    #[error("could not download {1}, the repository answered with {0}")]
+    Unavailable(StatusCode, String),
+}
+
+/// The file a maven repository lists the published versions of an artifact in, next to their directories
+const METADATA: &str = "maven-metadata.xml";
+
+pub fn publish(repository: Option<String>, dry_run: bool) -> Result<(), PublishError> {
+    let config = Config::find_and_open_config().map_err(PublishError::Config)?;
+    let repository = repository
+        .or_else(|| {
+            config
+                .publish()
+                .and_then(PublishConfig::repository)
+                .cloned()
+        })
+        .ok_or(PublishError::NoRepository)?;
+    let repository = repository.trim_end_matches('/');
+
+    // what gets published is the library other brews use, not a runnable jar with its lib next to it
+    config.fetch().map_err(PublishError::Fetch)?;
+    let jar = config.library_jar(&crate::config::get_root_path().unwrap());
+    config.sources_jar();
+    make_menu(MessageFormat::Human).map_err(PublishError::Menu)?;
+    config.javadoc_jar();
+    let pom = jar.with_extension("pom");
+    fs::write(&pom, config.pom()?)
+        .map_err(|error| PublishError::FileWrite(error, pom.display().to_string()))?;
+
+    let mut artifacts = vec![];
+    for artifact in [
+        jar.clone(),
+        config.artifact_path(Some("sources")),
+        config.artifact_path(Some("javadoc")),
+        pom,
+    ] {
+        artifacts.extend(write_checksums(&artifact)?);
+        artifacts.push(artifact);
+    }
+
+    let brew = config.brew();
+    let artifact_directory = format!("{}/{}", brew.group().replace('.', "/"), brew.name());
+    let directory = format!("{artifact_directory}/{}", brew.version());
+    // blends find the versions they can use in maven-metadata.xml, so it has to list this one too
+    let existing = if dry_run {
+        None
+    } else {
+        read_metadata(repository, &artifact_directory)?
+    };
+    let metadata = jar.with_file_name(METADATA);
+    let xml = merge_metadata(
+        existing.as_deref(),
+        brew.group(),
+        brew.name(),
+        &brew.version().to_string(),
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |since| since.as_secs() as i64),
+    )?;
+    fs::write(&metadata, xml)
+        .map_err(|error| PublishError::FileWrite(error, metadata.display().to_string()))?;
+    let mut metadata_artifacts = write_checksums(&metadata)?;
+    metadata_artifacts.push(metadata);
+
+    // the metadata goes up last, so it never lists a version whose artifacts aren't there yet
+    let uploads = [
+        (directory, artifacts),
+        (artifact_directory, metadata_artifacts),
+    ];
+    if dry_run {
+        for (directory, artifacts) in uploads {
+            for artifact in artifacts {
+                println!(
+                    "would upload {} to {repository}/{directory}/{}",
+                    artifact.display(),
+                    file_name(&artifact)
+                );
+            }
+        }
+        return Ok(());
+    }
+    for (directory, artifacts) in uploads {
+        if let Some(path) = repository.strip_prefix("file://") {
+            copy_artifacts(&artifacts, &expand_home(path).join(directory))?;
+        } else {
+            upload_artifacts(&artifacts, &format!("{repository}/{directory}"))?;
+        }
+    }
+    Ok(())
+}
+
+impl Config {
+    /// Generates a pom.xml describing the brew and its blends, using the versions `fetch` locked
+    /// since a version requirement like ^4.13.2 isn't a version maven understands
+    pub fn pom(&self) -> Result<String, PublishError> {
+        let lock = open_toml::<LockFile>(&get_lock_path()).ok();
+        let mut blends = self.blends().iter().collect::<Vec<_>>();
+        blends.sort_by_key(|(name, _)| *name);
+        let mut dependencies = vec![];
+        for (name, blend) in blends {
+            let (group_id, version) = if let Some(author) = blend.author() {
+                let package = lock
+                    .as_ref()
+                    .and_then(|lock| lock.find(name, author))
+                    .ok_or_else(|| PublishError::Unlocked(name.clone()))?;
+                (author.clone(), package.version().to_string())
+            } else if let Some(path) = blend.path() {
+                // path blends are relative to the brew declaring them
+                let root = crate::config::get_root_path().unwrap();
+                let dep = Config::open_config(root.join(path).join("Brew.toml"))
+                    .map_err(|error| PublishError::PathBlend(name.clone(), Box::new(error)))?;
+                (
+                    dep.brew().group().to_string(),
+                    dep.brew().version().to_string(),
+                )
+            } else {
+                // git blends only know their coordinates once fetch has cloned them
+                let package = lock
+                    .as_ref()
+                    .and_then(|lock| lock.find_local(name))
+                    .ok_or_else(|| PublishError::Unlocked(name.clone()))?;
+                (package.authors().to_string(), package.version().to_string())
+            };
+            dependencies.push(PomDependency {
+                group_id,
+                artifact_id: name.clone(),
+                version,
+                scope: (blend.scope() != MavenDependencyScopeType::Compile)
+                    .then(|| blend.scope().as_str()),
+                optional: blend.optional(),
+            });
+        }
+
+        let brew = self.brew();
+        let pom = Pom {
+            xmlns: "http://maven.apache.org/POM/4.0.0",
+            xmlns_xsi: "http://www.w3.org/2001/XMLSchema-instance",
+            schema_location:
+                "http://maven.apache.org/POM/4.0.0 https://maven.apache.org/xsd/maven-4.0.0.xsd",
+            model_version: "4.0.0",
+            group_id: brew.group().to_string(),
+            artifact_id: brew.name().to_string(),
+            version: brew.version().to_string(),
+            packaging: "jar",
+            name: brew.name().to_string(),
+            description: brew.description().cloned(),
+            url: brew.repository().cloned(),
+            licenses: brew.license().map(|license| PomLicenses {
+                license: PomLicense {
+                    name: license.clone(),
+                },
+            }),
+            developers: (!brew.authors().is_empty()).then(|| PomDevelopers {
+                developer: brew
+                    .authors()
+                    .iter()
+                    .map(|name| PomDeveloper { name: name.clone() })
+                    .collect(),
+            }),
+            scm: brew.repository().map(|url| PomScm { url: url.clone() }),
+            dependencies: (!dependencies.is_empty()).then_some(PomDependencies {
+                dependency: dependencies,
+            }),
+        };
+        let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        let mut serializer = quick_xml::se::Serializer::with_root(&mut xml, Some("project"))
+            .map_err(PublishError::Pom)?;
+        serializer.indent(' ', 2);
+        pom.serialize(serializer).map_err(PublishError::Pom)?;
+        xml.push('\n');
+        Ok(xml)
+    }
+}
+
+/// The repository's maven-metadata.xml for the brew in `directory`, None if it was never published there
+fn read_metadata(repository: &str, directory: &str) -> Result<Option<String>, PublishError> {
+    if let Some(path) = repository.strip_prefix("file://") {
+        let path = expand_home(path).join(directory).join(METADATA);
+        return match fs::read_to_string(&path) {
+            Ok(xml) => Ok(Some(xml)),
+            Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(error) => Err(PublishError::FileRead(error, path.display().to_string())),
+        };
+    }
+    let url = format!("{repository}/{directory}/{METADATA}");
+    runtime().block_on(async {
+        let response = authenticate(Client::new().get(&url))
+            .send()
+            .await
+            .map_err(|error| PublishError::Download(error, url.clone()))?;
+        match response.status() {
+            StatusCode::NOT_FOUND => Ok(None),
+            status if status.is_success() => response
+                .text()
+                .await
+                .map(Some)
+                .map_err(|error| PublishError::Download(error, url)),
+            status => Err(PublishError::Unavailable(status, url)),
+        }
+    })
+}
+
+/// Adds `version` to the `existing` maven-metadata.xml of an artifact (or starts a new one), with the newest
+/// version as latest and the newest version that isn't a prerelease as release
+fn merge_metadata(
+    existing: Option<&str>,
+    group: &str,
+    artifact: &str,
+    version: &str,
+    now: i64,
+) -> Result<String, PublishError> {
+    let mut metadata = existing
+        .map(quick_xml::de::from_str::<MavenMetadata>)
+        .transpose()
+        .map_err(PublishError::Metadata)?
+        .unwrap_or_default();
+    metadata.group_id = group.to_string();
+    metadata.artifact_id = artifact.to_string();
+    let versioning = &mut metadata.versioning;
+    if !versioning
+        .versions
+        .version
+        .iter()
+        .any(|known| known == version)
+    {
+        versioning.versions.version.push(version.to_string());
+    }
+    let parsed = versioning
+        .versions
+        .version
+        .iter()
+        .filter_map(|known| {
+            lenient_semver::Version::parse(known)
+                .ok()
+                .map(|parsed| (to_version(parsed), known.clone()))
+        })
+        .collect::<Vec<_>>();
+    versioning.latest = parsed.iter().max().map(|(_, known)| known.clone());
+    versioning.release = parsed
+        .iter()
+        .filter(|(parsed, _)| parsed.pre.is_empty())
+        .max()
+        .map(|(_, known)| known.clone());
+    let (year, month, day, hour, minute, second) = civil_time(now);
+    versioning.last_updated = format!("{year:04}{month:02}{day:02}{hour:02}{minute:02}{second:02}");
+
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    let mut serializer = quick_xml::se::Serializer::with_root(&mut xml, Some("metadata"))
+        .map_err(PublishError::Metadata)?;
+    serializer.indent(' ', 2);
+    metadata
+        .serialize(serializer)
+        .map_err(PublishError::Metadata)?;
+    xml.push('\n');
+    Ok(xml)
+}
+
+/// Writes the .md5, .sha1 and .sha256 files maven repositories expect next to every artifact
+fn write_checksums(artifact: &Path) -> Result<Vec<PathBuf>, PublishError> {
+    let bytes = fs::read(artifact)
+        .map_err(|error| PublishError::FileRead(error, artifact.display().to_string()))?;
+    [
+        ("md5", format!("{:x}", Md5::digest(&bytes))),
+        ("sha1", format!("{:x}", Sha1::digest(&bytes))),
+        ("sha256", format!("{:x}", Sha256::digest(&bytes))),
+    ]
+    .into_iter()
+    .map(|(extension, checksum)| {
+        let path = PathBuf::from(format!("{}.{extension}", artifact.display()));
+        fs::write(&path, checksum)
+            .map_err(|error| PublishError::FileWrite(error, path.display().to_string()))?;
+        Ok(path)
+    })
+    .collect()
+}
+
+fn copy_artifacts(artifacts: &[PathBuf], directory: &Path) -> Result<(), PublishError> {
+    fs::create_dir_all(directory)
+        .map_err(|error| PublishError::Copy(error, directory.display().to_string()))?;
+    for artifact in artifacts {
+        let destination = directory.join(file_name(artifact));
+        fs::copy(artifact, &destination)
+            .map_err(|error| PublishError::Copy(error, destination.display().to_string()))?;
+        println!("copied {}", destination.display());
+    }
+    Ok(())
+}
+
+/// PUTs every artifact
+fn upload_artifacts(artifacts: &[PathBuf], url: &str) -> Result<(), PublishError> {
+    runtime().block_on(async {
+        let client = Client::new();
+        for artifact in artifacts {
+            let bytes = fs::read(artifact)
+                .map_err(|error| PublishError::FileRead(error, artifact.display().to_string()))?;
+            let url = format!("{url}/{}", file_name(artifact));
+            let response = authenticate(client.put(&url).body(bytes))
+                .send()
+                .await
+                .map_err(|error| PublishError::Upload(error, url.clone()))?;
+            if !response.status().is_success() {
+                return Err(PublishError::Rejected(response.status(), url));
+            }
+            println!("uploaded {url}");
+        }
+        Ok(())
+    })
+}
+
+/// Authenticates with `BARISTA_PUBLISH_USERNAME` and `BARISTA_PUBLISH_PASSWORD` if they are set
+fn authenticate(request: RequestBuilder) -> RequestBuilder {
+    match std::env::var("BARISTA_PUBLISH_USERNAME") {
+        Ok(username) => {
+            request.basic_auth(username, std::env::var("BARISTA_PUBLISH_PASSWORD").ok())
+        }
+        Err(_) => request,
+    }
+}
+
+fn runtime() -> Runtime {
+    Builder::new_multi_thread()
+        .worker_threads(1)
+        .enable_all()
+        .build()
+        .unwrap()
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
+fn expand_home(path: &str) -> PathBuf {
+    match path.strip_prefix("~/") {
+        Some(path) => dirs::home_dir()
+            .expect("no home directory to publish to")
+            .join(path),
+        None => PathBuf::from(path),
+    }
+}
+
+/// the parts of https://maven.apache.org/xsd/repository-metadata-1.1.0.xsd barista keeps up to date
+#[derive(Serialize, Deserialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+struct MavenMetadata {
+    #[serde(default)]
+    group_id: String,
+    #[serde(default)]
+    artifact_id: String,
+    #[serde(default)]
+    versioning: MetadataVersioning,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+struct MetadataVersioning {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    latest: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    release: Option<String>,
+    #[serde(default)]
+    versions: MetadataVersions,
+    #[serde(default)]
+    last_updated: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+struct MetadataVersions {
+    #[serde(default)]
+    version: Vec<String>,
+}
+
+/// the parts of https://maven.apache.org/xsd/maven-4.0.0.xsd barista fills in
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct Pom {
+    #[serde(rename = "@xmlns")]
+    xmlns: &'static str,
+    #[serde(rename = "@xmlns:xsi")]
+    xmlns_xsi: &'static str,
+    #[serde(rename = "@xsi:schemaLocation")]
+    schema_location: &'static str,
+    model_version: &'static str,
+    group_id: String,
+    artifact_id: String,
+    version: String,
+    packaging: &'static str,
+    name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    licenses: Option<PomLicenses>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    developers: Option<PomDevelopers>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    scm: Option<PomScm>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    dependencies: Option<PomDependencies>,
+}
+
+#[derive(Serialize, Debug)]
+struct PomLicenses {
+    license: PomLicense,
+}
+
+#[derive(Serialize, Debug)]
+struct PomLicense {
+    name: String,
+}
+
+#[derive(Serialize, Debug)]
+struct PomDevelopers {
+    developer: Vec<PomDeveloper>,
+}
+
+#[derive(Serialize, Debug)]
+struct PomDeveloper {
+    name: String,
+}
+
+#[derive(Serialize, Debug)]
+struct PomScm {
+    url: String,
+}
+
+#[derive(Serialize, Debug)]
+struct PomDependencies {
+    dependency: Vec<PomDependency>,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct PomDependency {
+    group_id: String,
+    artifact_id: String,
+    version: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    scope: Option<&'static str>,
+    /// optional blends are only pulled in by brews that turn on one of the features using them
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    optional: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PUBLISHED: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<metadata>
+  <groupId>com.example</groupId>
+  <artifactId>core</artifactId>
+  <versioning>
+    <latest>2.0.0-rc.1</latest>
+    <release>1.2.0</release>
+    <versions>
+      <version>1.2.0</version>
+      <version>2.0.0-rc.1</version>
+    </versions>
+    <lastUpdated>20200101000000</lastUpdated>
+  </versioning>
+</metadata>
+"#;
+
+    #[test]
+    fn metadata_of_first_publish() {
+        let xml = merge_metadata(None, "com.example", "core", "1.0.0", 1700000000).unwrap();
+        assert!(xml.contains("<groupId>com.example</groupId>"));
+        assert!(xml.contains("<artifactId>core</artifactId>"));
+        assert!(xml.contains("<latest>1.0.0</latest>"));
+        assert!(xml.contains("<release>1.0.0</release>"));
+        assert!(xml.contains("<versions>\n      <version>1.0.0</version>\n    </versions>"));
+        assert!(xml.contains("<lastUpdated>20231114221320</lastUpdated>"));
+    }
+
+    #[test]
+    fn metadata_keeps_published_versions() {
+        let xml = merge_metadata(Some(PUBLISHED), "com.example", "core", "1.1.0", 0).unwrap();
+        assert!(xml.contains(
+            "<version>1.2.0</version>\n      <version>2.0.0-rc.1</version>\n      <version>1.1.0</version>"
+        ));
+        // the newest version is latest even when an older one is published later
+        assert!(xml.contains("<latest>2.0.0-rc.1</latest>"));
+        assert!(xml.contains("<release>1.2.0</release>"));
+        assert!(xml.contains("<lastUpdated>19700101000000</lastUpdated>"));
+    }
+
+    #[test]
+    fn metadata_lists_republished_version_once() {
+        let xml = merge_metadata(Some(PUBLISHED), "com.example", "core", "1.2.0", 0).unwrap();
+        assert_eq!(xml.matches("<version>1.2.0</version>").count(), 1);
+    }
+}
@@ -0,0 +1,67 @@
+//! resources.rs handles the non java files of a brew (.properties, templates, META-INF/services ...)
+//! that have to end up next to the classes, both in `bin` and in jars
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    config::{Config, ResourcesConfig},
+    utils::walk_dir,
+};
+
+impl Config {
+    /// Every resource of the brew rooted at brew_root, as its path relative to the resource directory and its (filtered) contents
+    pub fn resource_files(&self, brew_root: &Path) -> Vec<(PathBuf, Vec<u8>)> {
+        let default_resources = ResourcesConfig::default();
+        let resources = self.resources().unwrap_or(&default_resources);
+        let resource_root = brew_root.join(resources.path());
+        walk_dir(&resource_root)
+            .into_iter()
+            .map(|path| {
+                let bytes = fs::read(&path)
+                    .unwrap_or_else(|e| panic!("could not read resource {}: {e}", path.display()));
+                let bytes = if resources.filter() {
+                    self.filter_resource(bytes)
+                } else {
+                    bytes
+                };
+                (path.strip_prefix(&resource_root).unwrap().to_path_buf(), bytes)
+            })
+            .collect()
+    }
+
+    /// Copies the resources into the output directory, so they are on the classpath next to the classes
+    pub fn copy_resources(&self, brew_root: &Path, out: &Path) {
+        for (path, bytes) in self.resource_files(brew_root) {
+            let destination = out.join(path);
+            if let Some(parent) = destination.parent() {
+                fs::create_dir_all(parent).unwrap_or_else(|e| {
+                    panic!("could not create resource directory {}: {e}", parent.display())
+                });
+            }
+            fs::write(&destination, bytes).unwrap_or_else(|e| {
+                panic!("could not copy resource to {}: {e}", destination.display())
+            });
+        }
+    }
+
+    /// Replaces `${brew.name}`, `${brew.version}`, `${brew.group}` and `${brew.description}`,
+    /// anything that isn't utf-8 text is left alone
+    fn filter_resource(&self, bytes: Vec<u8>) -> Vec<u8> {
+        match String::from_utf8(bytes) {
+            Ok(text) => {
+                let brew = self.brew();
+                text.replace("${brew.name}", brew.name())
+                    .replace("${brew.version}", &brew.version().to_string())
+                    .replace("${brew.group}", brew.group())
+                    .replace(
+                        "${brew.description}",
+                        brew.description().map_or("", String::as_str),
+                    )
+                    .into_bytes()
+            }
+            Err(e) => e.into_bytes(),
+        }
+    }
+}
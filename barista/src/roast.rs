@@ -1,50 +1,147 @@
 // javac -cp lib/* src/Main.java
-use std::{
-    path::PathBuf,
-    process::{Command, Stdio},
-};
+use std::{env, ffi::OsString, io, process::Command};
 
 // // javac -c lib/* main & java -c lib/* main
-use javaup::config;
+use crate::{
+    config::{BinNotFoundError, Config, FeatureOptions, OpenConfigError, RoastConfig},
+    daemon,
+    diagnostics::{self, MessageFormat},
+    fetch::FetchError,
+    module::module_name,
+    toolchain::{self, ToolchainError},
+};
+
+#[derive(Debug, thiserror::Error)]
+pub enum RoastError {
+    #[error("{0}")]
+    Toolchain(ToolchainError),
+    #[error("{0}")]
+    Config(OpenConfigError),
+    #[error("{0}")]
+    Fetch(FetchError),
+    #[error("{0}")]
+    Bin(BinNotFoundError),
+    #[error("error running javac: {0}")]
+    Spawn(io::Error),
+    #[error("could not compile the brew due to {0} error(s)")]
+    Compile(usize),
+}
+
+/// How roast compiles, the default is what every command that packages the brew uses
+#[derive(Debug, Default, Clone)]
+pub struct RoastOptions {
+    /// compile with all debug information (-g), so a debugger can show local variables
+    pub debug: bool,
+    /// use the blends already in lib instead of fetching them again
+    pub skip_fetch: bool,
+    /// how compiler errors and warnings are printed
+    pub message_format: MessageFormat,
+    /// the features of the brew to build with
+    pub features: FeatureOptions,
+}
 
-use crate::{config::Config, utils::unless_exists};
+pub fn roast(bin: Option<String>, options: RoastOptions) -> Result<(), RoastError> {
+    let config = Config::find_and_open_config_with(
+        &options.features.features,
+        !options.features.no_default_features,
+    )
+    .map_err(RoastError::Config)?;
+    if !options.skip_fetch {
+        config.fetch().map_err(RoastError::Fetch)?;
+    }
+    let root_path = crate::config::get_root_path().unwrap();
+    let root = root_path.display();
 
-pub fn roast(bin: Option<String>) {
-    let config = Config::find_and_open_config().unwrap();
-    config.fetch();
-    let binding = crate::config::get_root_path().unwrap();
-    let root = binding.display();
-    let mut source = binding.clone();
+    let jdk = toolchain::find(&config).map_err(RoastError::Toolchain)?;
+    let library = config.is_library(bin.as_deref(), &config.source_root(&root_path));
+    // a library has no bin to start from, so all of it is compiled
+    let sources = if library {
+        config
+            .library_files(&root_path)
+            .into_iter()
+            .filter(|file| file.extension().is_some_and(|ext| ext == "java"))
+            .collect()
+    } else {
+        let binding = config
+            .bin_source(bin, &config.source_root(&root_path))
+            .map_err(RoastError::Bin)?;
+        let bin_path = binding.display();
+        if options.message_format == MessageFormat::Human {
+            println!("{bin_path}");
+        }
+        let bin_path = config.source_root(&root_path).join(bin_path.to_string());
+        if !bin_path.exists() {
+            return Err(RoastError::Bin(BinNotFoundError::NoSource(
+                bin_path.display().to_string(),
+            )));
+        }
+        vec![bin_path]
+    };
+    // keeps the class files and the api the brew is checked against at the toolchain's java version
+    let mut args: Vec<OsString> = toolchain::release_args(&jdk)
+        .into_iter()
+        .map(OsString::from)
+        .collect();
+    if module_name(&config.source_root(&root_path)).is_some() {
+        let paths = config.module_paths();
+        args.extend([
+            "--module-path".into(),
+            paths.module_path(),
+            "-cp".into(),
+            paths.class_path(),
+        ]);
+        if !library {
+            args.push(
+                config
+                    .source_root(&root_path)
+                    .join("module-info.java")
+                    .into(),
+            );
+        }
+    } else {
+        // lib/* is only expanded by the javac launcher, not by the compiler the daemon uses
+        args.extend(["-cp".into(), config.lib_class_path()]);
+    }
+    if options.debug {
+        args.push("-g".into());
+    }
+    // features can add source directories next to src
+    let source_roots = if library {
+        config.library_roots(&root_path)
+    } else {
+        config.source_roots(&root_path, config.brew().source())
+    };
+    let source_path =
+        env::join_paths(source_roots).expect("brew path can't be used in a source path");
+    args.extend(["--source-path".into(), source_path]);
+    args.extend(sources.into_iter().map(OsString::from));
+    args.extend(["-d".into(), format!("{root}/bin").into()]);
 
-    let java_config = config::config_file();
-    let mut java_bin = config::jdkdir();
-    java_bin.push(java_config.default_jdk.clone().unwrap().distribution);
-    java_bin.push(java_config.default_jdk.unwrap().version);
-    java_bin.push("bin");
-    #[cfg(target_os = "windows")]
-    java_bin.push("javac.exe");
-    #[cfg(not(target_os = "windows"))]
-    java_bin.push("javac");
-    let binding = bin
-        .and_then(|bin| config.find_bin(bin).ok())
-        .unwrap_or(PathBuf::from("Main.java"));
-    let bin_path = binding.display();
-    let mut binding = Command::new(java_bin);
-    println!("{bin_path}");
-    let bin_path =
-        PathBuf::from_iter(([root.to_string(), "src".to_string(), bin_path.to_string()]));
-    unless_exists(bin_path.clone(), || panic!("not bin target found"));
-    let bin_path = bin_path.display();
-    let javac_ex = binding
-        .arg("-cp")
-        .arg(format!("{root}/lib/*",))
-        .arg("--source-path")
-        .arg(format!("{root}/src"))
-        .arg(format!("{bin_path}"))
-        .arg("-d")
-        .arg(format!("{root}/bin"))
-        .stdout(Stdio::inherit())
-        .stderr(Stdio::inherit());
-    // println!("{:?}", javac_ex);
-    javac_ex.status().unwrap();
+    let daemon = config.roast_config().is_some_and(RoastConfig::daemon);
+    let compiled = if daemon {
+        daemon::compile(&jdk, &root_path, &args)
+            .map_err(|e| eprintln!("compile daemon unavailable, falling back to javac: {e}"))
+            .ok()
+    } else {
+        None
+    };
+    let (code, output) = match compiled {
+        Some(compiled) => compiled,
+        None => {
+            let output = Command::new(jdk.javac())
+                .args(&args)
+                .output()
+                .map_err(RoastError::Spawn)?;
+            (
+                output.status.code().unwrap_or(1),
+                diagnostics::tool_output(&output),
+            )
+        }
+    };
+    let errors = diagnostics::report("javac", &output, options.message_format, &root_path);
+    if code != 0 {
+        return Err(RoastError::Compile(errors));
+    }
+    config.copy_resources(&root_path, &root_path.join("bin"));
+    Ok(())
 }
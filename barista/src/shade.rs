@@ -0,0 +1,257 @@
+//! shade.rs relocates packages inside fat jars, so a bundled blend can't clash with another copy of itself on the user's classpath
+use std::collections::HashMap;
+
+#[derive(Debug, thiserror::Error)]
+pub enum ClassFileError {
+    #[error("not a class file (bad magic number)")]
+    BadMagic,
+    #[error("class file ended in the middle of the constant pool")]
+    Truncated,
+    #[error("unknown constant pool tag {0}")]
+    UnknownTag(u8),
+}
+
+pub struct Relocator {
+    // every relocation in both its internal (com/google) and binary (com.google) form, longest first
+    patterns: Vec<(Vec<u8>, Vec<u8>)>,
+}
+
+impl Relocator {
+    pub fn new(relocations: &HashMap<String, String>) -> Self {
+        let mut patterns: Vec<(Vec<u8>, Vec<u8>)> = relocations
+            .iter()
+            .flat_map(|(from, to)| {
+                [
+                    (
+                        format!("{}/", from.replace('.', "/")).into_bytes(),
+                        format!("{}/", to.replace('.', "/")).into_bytes(),
+                    ),
+                    (
+                        format!("{from}.").into_bytes(),
+                        format!("{to}.").into_bytes(),
+                    ),
+                ]
+            })
+            .collect();
+        patterns.sort_by(|(a, _), (b, _)| b.len().cmp(&a.len()).then(a.cmp(b)));
+        Self { patterns }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.patterns.is_empty()
+    }
+
+    /// Relocates jar entry names and text resources such as service files, which name classes in their binary form
+    pub fn relocate_str(&self, text: &str) -> String {
+        // relocations are ascii so splitting them out of valid utf-8 keeps it valid
+        String::from_utf8(self.relocate_bytes(text.as_bytes()))
+            .expect("relocating valid utf-8 should stay valid utf-8")
+    }
+
+    /// Rewrites every string in the constant pool of a class file, this is where all class names, descriptors and signatures live
+    pub fn relocate_class(&self, class: &[u8]) -> Result<Vec<u8>, ClassFileError> {
+        if class.get(..4) != Some(&[0xCA, 0xFE, 0xBA, 0xBE]) {
+            return Err(ClassFileError::BadMagic);
+        }
+        let read_u16 = |at: usize| {
+            class
+                .get(at..at + 2)
+                .map(|bytes| u16::from_be_bytes([bytes[0], bytes[1]]))
+                .ok_or(ClassFileError::Truncated)
+        };
+        // magic, minor and major version are copied as is
+        let mut out = class[..8].to_vec();
+        let count = read_u16(8)?;
+        out.extend_from_slice(&count.to_be_bytes());
+        let mut at = 10;
+        let mut index = 1;
+        while index < count {
+            let tag = *class.get(at).ok_or(ClassFileError::Truncated)?;
+            let size = match tag {
+                // utf8
+                1 => {
+                    let len = read_u16(at + 1)? as usize;
+                    let text = class
+                        .get(at + 3..at + 3 + len)
+                        .ok_or(ClassFileError::Truncated)?;
+                    let text = self.relocate_bytes(text);
+                    out.push(tag);
+                    out.extend_from_slice(&(text.len() as u16).to_be_bytes());
+                    out.extend_from_slice(&text);
+                    at += 3 + len;
+                    index += 1;
+                    continue;
+                }
+                // class, string, method type, module, package
+                7 | 8 | 16 | 19 | 20 => 3,
+                // method handle
+                15 => 4,
+                // integer, float, field/method/interface method refs, name and type, dynamic, invoke dynamic
+                3 | 4 | 9 | 10 | 11 | 12 | 17 | 18 => 5,
+                // long and double take up two slots in the pool
+                5 | 6 => {
+                    index += 1;
+                    9
+                }
+                tag => return Err(ClassFileError::UnknownTag(tag)),
+            };
+            out.extend_from_slice(class.get(at..at + size).ok_or(ClassFileError::Truncated)?);
+            at += size;
+            index += 1;
+        }
+        // everything after the constant pool only refers to it by index
+        out.extend_from_slice(&class[at..]);
+        Ok(out)
+    }
+
+    fn relocate_bytes(&self, text: &[u8]) -> Vec<u8> {
+        let mut out = Vec::with_capacity(text.len());
+        let mut at = 0;
+        while at < text.len() {
+            // only match at the start of a name, descriptors put an L in front of class names
+            let boundary = at == 0
+                || text[at - 1] == b'L'
+                || !(text[at - 1].is_ascii_alphanumeric()
+                    || matches!(text[at - 1], b'_' | b'$' | b'/' | b'.'));
+            if let Some((from, to)) = self
+                .patterns
+                .iter()
+                .find(|(from, _)| boundary && text[at..].starts_with(from))
+            {
+                out.extend_from_slice(to);
+                at += from.len();
+            } else {
+                out.push(text[at]);
+                at += 1;
+            }
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn relocator() -> Relocator {
+        Relocator::new(&HashMap::from([(
+            "com.google.gson".to_string(),
+            "example.shaded.gson".to_string(),
+        )]))
+    }
+
+    fn utf8(text: &str) -> Vec<u8> {
+        let mut entry = vec![1];
+        entry.extend_from_slice(&(text.len() as u16).to_be_bytes());
+        entry.extend_from_slice(text.as_bytes());
+        entry
+    }
+
+    /// A class file with the given constant pool entries and `tail` as everything after the pool
+    fn class_file(entries: &[Vec<u8>], count: u16, tail: &[u8]) -> Vec<u8> {
+        let mut class = vec![0xCA, 0xFE, 0xBA, 0xBE, 0, 0, 0, 61];
+        class.extend_from_slice(&count.to_be_bytes());
+        for entry in entries {
+            class.extend_from_slice(entry);
+        }
+        class.extend_from_slice(tail);
+        class
+    }
+
+    /// Walks the constant pool like the jvm does, returning its utf8 strings and what comes after it
+    fn read_pool(class: &[u8]) -> (Vec<String>, Vec<u8>) {
+        let count = u16::from_be_bytes([class[8], class[9]]);
+        let (mut at, mut index, mut strings) = (10, 1, vec![]);
+        while index < count {
+            match class[at] {
+                1 => {
+                    let len = u16::from_be_bytes([class[at + 1], class[at + 2]]) as usize;
+                    strings.push(String::from_utf8(class[at + 3..at + 3 + len].to_vec()).unwrap());
+                    at += 3 + len;
+                }
+                7 | 8 => at += 3,
+                12 => at += 5,
+                5 | 6 => {
+                    at += 9;
+                    index += 1;
+                }
+                tag => panic!("unexpected tag {tag}"),
+            }
+            index += 1;
+        }
+        (strings, class[at..].to_vec())
+    }
+
+    #[test]
+    fn relocates_names_descriptors_and_binary_names() {
+        let tail = [0x00, 0x21, 0x00, 0x02, 0x00, 0x00];
+        let class = class_file(
+            &[
+                utf8("com/google/gson/Gson"),
+                vec![7, 0, 1],
+                // a long takes two slots, so the entry after it is index 5
+                vec![5, 0, 0, 0, 0, 0, 0, 0, 42],
+                utf8("(Lcom/google/gson/Gson;Ljava/lang/String;)V"),
+                utf8("com.google.gson.Gson"),
+                vec![12, 0, 4, 0, 5],
+            ],
+            8,
+            &tail,
+        );
+        let relocated = relocator().relocate_class(&class).unwrap();
+        let (strings, rest) = read_pool(&relocated);
+        assert_eq!(
+            strings,
+            [
+                "example/shaded/gson/Gson",
+                "(Lexample/shaded/gson/Gson;Ljava/lang/String;)V",
+                "example.shaded.gson.Gson",
+            ]
+        );
+        assert_eq!(rest, tail);
+        // the long's bytes are copied as they are
+        assert!(relocated
+            .windows(9)
+            .any(|entry| entry == [5, 0, 0, 0, 0, 0, 0, 0, 42]));
+    }
+
+    #[test]
+    fn only_relocates_at_the_start_of_a_name() {
+        let relocator = relocator();
+        for text in [
+            "notcom/google/gson/Gson",
+            "org/com/google/gson/Gson",
+            "my.com.google.gson.Gson",
+            "com/google/gsonx/Gson",
+        ] {
+            assert_eq!(relocator.relocate_str(text), text);
+        }
+        assert_eq!(
+            relocator.relocate_str("[Lcom/google/gson/Gson;"),
+            "[Lexample/shaded/gson/Gson;"
+        );
+        assert_eq!(
+            relocator.relocate_str("com.google.gson.Gson\ncom.google.gson.internal.Excluder\n"),
+            "example.shaded.gson.Gson\nexample.shaded.gson.internal.Excluder\n"
+        );
+    }
+
+    #[test]
+    fn rejects_broken_class_files() {
+        let relocator = relocator();
+        assert!(matches!(
+            relocator.relocate_class(b"PK\x03\x04nope"),
+            Err(ClassFileError::BadMagic)
+        ));
+        let truncated = class_file(&[utf8("com/google/gson/Gson")], 3, &[]);
+        assert!(matches!(
+            relocator.relocate_class(&truncated),
+            Err(ClassFileError::Truncated)
+        ));
+        let unknown = class_file(&[vec![2, 0, 0]], 2, &[]);
+        assert!(matches!(
+            relocator.relocate_class(&unknown),
+            Err(ClassFileError::UnknownTag(2))
+        ));
+    }
+}
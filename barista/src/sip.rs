@@ -8,7 +8,170 @@
 // this is what needed for junit5, (defualt)
 //
 // we will probably overiding this to use junit4 in Brew.toml
+//
+// for now the runner is picked from the blends: the junit 5 console launcher if it is a blend, otherwise junit 4's JUnitCore
+use std::{
+    env, fs, io,
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+};
+
+use zip::ZipArchive;
+
+use crate::{
+    brew::DebugOptions,
+    config::{Config, OpenConfigError},
+    diagnostics::{self, MessageFormat},
+    fetch::FetchError,
+    toolchain::{self, ToolchainError},
+    utils::{declared_package, walk_dir},
+};
+
+/// Where in `bin` the tests are compiled to, so they stay out of the brew's jars
+pub const TEST_DIR: &str = "test-classes";
+
+const JUNIT5_CONSOLE: &str = "org.junit.platform.console.ConsoleLauncher";
+const JUNIT4_CORE: &str = "org.junit.runner.JUnitCore";
+
+#[derive(Debug, thiserror::Error)]
+pub enum SipError {
+    #[error("{0}")]
+    Config(OpenConfigError),
+    #[error("{0}")]
+    Toolchain(ToolchainError),
+    #[error("{0}")]
+    Fetch(FetchError),
+    #[error("no tests found in {0}")]
+    NoTests(String),
+    #[error(
+        "no test runner found, add junit (4) or junit-platform-console-standalone (5) as a blend"
+    )]
+    NoRunner,
+    #[error("error running {1}: {0}")]
+    Spawn(io::Error, String),
+    #[error("could not compile the tests due to {0} error(s)")]
+    Compile(usize),
+}
+
+/// Compiles the tests in `src/test` (`src/test/java` for the maven layout) and runs them, returning the exit code of the test runner
+pub fn sip(
+    search: Option<String>,
+    debug: DebugOptions,
+    skip_fetch: bool,
+    message_format: MessageFormat,
+) -> Result<i32, SipError> {
+    let config = Config::find_and_open_config().map_err(SipError::Config)?;
+    if !skip_fetch {
+        config.fetch().map_err(SipError::Fetch)?;
+    }
+    let root = crate::config::get_root_path().unwrap();
+    let test_root = config.test_root(&root);
+    let tests = walk_dir(&test_root)
+        .into_iter()
+        .filter(|path| {
+            path.extension()
+                .is_some_and(|extension| extension == "java")
+        })
+        .collect::<Vec<_>>();
+    if tests.is_empty() {
+        return Err(SipError::NoTests(test_root.display().to_string()));
+    }
+
+    let jdk = toolchain::find(&config).map_err(SipError::Toolchain)?;
+
+    // the classes under test are found through the source path
+    let test_classes = root.join("bin").join(TEST_DIR);
+    let mut javac = Command::new(jdk.javac());
+    javac
+        .args(toolchain::release_args(&jdk))
+        .arg("-cp")
+        .arg(env::join_paths(config.test_jars()).expect("blend path can't be used in a classpath"))
+        .arg("--source-path")
+        .arg(
+            env::join_paths(config.source_roots(&root, config.brew().source()))
+                .expect("brew path can't be used in a source path"),
+        )
+        .arg("-d")
+        .arg(&test_classes);
+    if debug.enabled() {
+        javac.arg("-g");
+    }
+    let output = javac
+        .args(&tests)
+        .output()
+        .map_err(|error| SipError::Spawn(error, "javac".to_string()))?;
+    let errors = diagnostics::report(
+        "javac",
+        &diagnostics::tool_output(&output),
+        message_format,
+        &root,
+    );
+    if !output.status.success() {
+        return Err(SipError::Compile(errors));
+    }
+    config.copy_resources(&root, &root.join("bin"));
+
+    let mut java = Command::new(jdk.java());
+    if let Some(agent) = debug.jvm_arg() {
+        java.arg(agent);
+    }
+    let test_jars = config.test_jars();
+    // the tests come first so test resources win over the brew's own
+    let class_path = env::join_paths(
+        [test_classes.clone(), root.join("bin")]
+            .into_iter()
+            .chain(test_jars.iter().cloned()),
+    )
+    .expect("brew path can't be used in a classpath");
+    java.arg("-cp").arg(class_path);
+    if has_class(&test_jars, JUNIT5_CONSOLE) {
+        java.arg(JUNIT5_CONSOLE)
+            .arg("--class-path")
+            .arg(&test_classes)
+            .arg("--scan-class-path");
+        if let Some(search) = search {
+            java.arg("--include-classname").arg(format!(".*{search}.*"));
+        }
+    } else if has_class(&test_jars, JUNIT4_CORE) {
+        let classes = tests
+            .iter()
+            .filter_map(|path| test_class(path))
+            .filter(|class| search.as_ref().is_none_or(|search| class.contains(search)))
+            .collect::<Vec<_>>();
+        if classes.is_empty() {
+            return Err(SipError::NoTests(test_root.display().to_string()));
+        }
+        java.arg(JUNIT4_CORE).args(classes);
+    } else {
+        return Err(SipError::NoRunner);
+    }
+    let status = java
+        .stdout(Stdio::inherit())
+        .stderr(Stdio::inherit())
+        .status()
+        .map_err(|error| SipError::Spawn(error, "java".to_string()))?;
+    Ok(status.code().unwrap_or(1))
+}
+
+/// The class a test source declares, if it has any tests (JUnitCore fails on classes without them)
+fn test_class(path: &Path) -> Option<String> {
+    let source = fs::read_to_string(path).ok()?;
+    if !source.contains("@Test") {
+        return None;
+    }
+    let class = path.file_stem()?.to_string_lossy();
+    Some(match declared_package(&source) {
+        Some(package) => format!("{package}.{class}"),
+        None => class.to_string(),
+    })
+}
 
-pub fn sip(search: Option<String>) {
-    todo!()
+fn has_class(jars: &[PathBuf], class: &str) -> bool {
+    let entry = format!("{}.class", class.replace('.', "/"));
+    jars.iter().any(|jar| {
+        fs::File::open(jar)
+            .map_err(zip::result::ZipError::Io)
+            .and_then(ZipArchive::new)
+            .is_ok_and(|mut archive| archive.by_name(&entry).is_ok())
+    })
 }
@@ -0,0 +1,222 @@
+//! template.rs finds the templates `barista craft` starts brews from, directories of files whose contents
+//! and paths can use `{{name}}` and `{{package}}`, and copies them into a new brew
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    process::{self, Command, Stdio},
+};
+
+use crate::utils::walk_dir;
+
+/// A command line app, what craft makes unless told otherwise
+pub const DEFAULT_TEMPLATE: &str = "bin";
+
+const BUILTIN_TEMPLATES: &[(&str, &[(&str, &str)])] = &[
+    (
+        "bin",
+        &[
+            ("Brew.toml", include_str!("../templates/bin/Brew.toml")),
+            (
+                "src/Main.java",
+                include_str!("../templates/bin/src/Main.java"),
+            ),
+        ],
+    ),
+    (
+        "lib",
+        &[
+            ("Brew.toml", include_str!("../templates/lib/Brew.toml")),
+            (
+                "src/main/java/{{package}}/Library.java",
+                include_str!("../templates/lib/src/main/java/{{package}}/Library.java"),
+            ),
+            (
+                "src/test/java/{{package}}/LibraryTest.java",
+                include_str!("../templates/lib/src/test/java/{{package}}/LibraryTest.java"),
+            ),
+        ],
+    ),
+];
+
+#[derive(Debug, thiserror::Error)]
+pub enum TemplateError {
+    #[error("no template named {0}, templates: {1}")]
+    Unknown(String, String),
+    #[error("template {0} is not a directory")]
+    NotADirectory(String),
+    #[error("error reading template file {1}: {0}")]
+    Read(io::Error, String),
+    #[error("error writing {1}: {0}")]
+    Write(io::Error, String),
+    #[error("could not clone template {0}")]
+    Clone(String),
+}
+
+/// Where a template comes from
+enum Source {
+    Builtin(&'static [(&'static str, &'static str)]),
+    Dir(PathBuf),
+    /// a checkout of a git template, removed once it is copied
+    Git(PathBuf),
+}
+
+/// The values the placeholders of a template are replaced with
+pub struct Placeholders {
+    name: String,
+    package: String,
+}
+
+impl Placeholders {
+    /// `package` is the brew's name turned into a java package, ie my-app becomes my_app
+    pub fn new(name: &str) -> Self {
+        let mut package = name
+            .to_lowercase()
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+            .collect::<String>();
+        if package.starts_with(|c: char| c.is_ascii_digit()) {
+            package.insert(0, '_');
+        }
+        Self {
+            name: name.to_string(),
+            package,
+        }
+    }
+
+    fn fill(&self, text: &str) -> String {
+        text.replace("{{name}}", &self.name)
+            .replace("{{package}}", &self.package)
+    }
+
+    /// In paths the package is a directory per part, as javac expects
+    fn fill_path(&self, path: &str) -> PathBuf {
+        PathBuf::from(
+            path.replace("{{name}}", &self.name)
+                .replace("{{package}}", &self.package.replace('.', "/")),
+        )
+    }
+}
+
+/// The user's own templates, a directory per template
+fn user_templates() -> Option<PathBuf> {
+    Some(dirs::home_dir()?.join(".barista").join("templates"))
+}
+
+/// Every template that can be used by name, the user's own first
+pub fn available() -> Vec<String> {
+    let mut templates = user_templates()
+        .and_then(|dir| fs::read_dir(dir).ok())
+        .map(|entries| {
+            entries
+                .filter_map(Result::ok)
+                .filter(|entry| entry.path().is_dir())
+                .map(|entry| entry.file_name().to_string_lossy().to_string())
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+    templates.sort();
+    for (name, _) in BUILTIN_TEMPLATES {
+        if !templates.iter().any(|template| template == name) {
+            templates.push(name.to_string());
+        }
+    }
+    templates
+}
+
+/// A template is a git url, a path to a directory (with a `/` in it, ie ./template),
+/// or the name of one of `~/.barista/templates` or a built-in one, in that order
+fn find(template: &str) -> Result<Source, TemplateError> {
+    let is_git = ["https://", "http://", "git@", "ssh://", "git://"]
+        .iter()
+        .any(|prefix| template.starts_with(prefix))
+        || template.ends_with(".git");
+    if is_git {
+        let checkout = std::env::temp_dir().join(format!("barista-template-{}", process::id()));
+        fs::remove_dir_all(&checkout).ok();
+        let cloned = Command::new("git")
+            .arg("clone")
+            .arg("--depth")
+            .arg("1")
+            .arg(template)
+            .arg(&checkout)
+            .stdout(Stdio::inherit())
+            .stderr(Stdio::inherit())
+            .status()
+            .is_ok_and(|status| status.success());
+        if !cloned {
+            return Err(TemplateError::Clone(template.to_string()));
+        }
+        return Ok(Source::Git(checkout));
+    }
+    if template.contains(['/', '\\']) {
+        if !Path::new(template).is_dir() {
+            return Err(TemplateError::NotADirectory(template.to_string()));
+        }
+        return Ok(Source::Dir(PathBuf::from(template)));
+    }
+    if let Some(dir) = user_templates()
+        .map(|dir| dir.join(template))
+        .filter(|dir| dir.is_dir())
+    {
+        return Ok(Source::Dir(dir));
+    }
+    BUILTIN_TEMPLATES
+        .iter()
+        .find(|(name, _)| *name == template)
+        .map(|(_, files)| Source::Builtin(files))
+        .ok_or_else(|| TemplateError::Unknown(template.to_string(), available().join(", ")))
+}
+
+/// Copies the template into `root`, filling in its placeholders
+pub fn apply(
+    template: &str,
+    root: &Path,
+    placeholders: &Placeholders,
+) -> Result<(), TemplateError> {
+    match find(template)? {
+        Source::Builtin(files) => {
+            for (path, contents) in files {
+                write(
+                    &root.join(placeholders.fill_path(path)),
+                    placeholders.fill(contents).into_bytes(),
+                )?;
+            }
+            Ok(())
+        }
+        Source::Dir(dir) => copy(&dir, root, placeholders),
+        Source::Git(checkout) => {
+            let copied = copy(&checkout, root, placeholders);
+            fs::remove_dir_all(&checkout).ok();
+            copied
+        }
+    }
+}
+
+fn copy(dir: &Path, root: &Path, placeholders: &Placeholders) -> Result<(), TemplateError> {
+    for file in walk_dir(dir) {
+        let relative = file.strip_prefix(dir).unwrap_or(&file);
+        // the history of a git template isn't part of the brew
+        if relative.starts_with(".git") {
+            continue;
+        }
+        let contents =
+            fs::read(&file).map_err(|e| TemplateError::Read(e, file.display().to_string()))?;
+        // binary files (ie a wrapper jar or an icon) are copied as they are
+        let contents = match String::from_utf8(contents) {
+            Ok(text) => placeholders.fill(&text).into_bytes(),
+            Err(binary) => binary.into_bytes(),
+        };
+        write(
+            &root.join(placeholders.fill_path(&relative.to_string_lossy())),
+            contents,
+        )?;
+    }
+    Ok(())
+}
+
+fn write(path: &Path, contents: Vec<u8>) -> Result<(), TemplateError> {
+    path.parent()
+        .map_or(Ok(()), fs::create_dir_all)
+        .and_then(|()| fs::write(path, contents))
+        .map_err(|e| TemplateError::Write(e, path.display().to_string()))
+}
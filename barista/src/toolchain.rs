@@ -0,0 +1,70 @@
+//! toolchain.rs finds the jdk a brew is built and run with, the one pinned by `[toolchain]` in Brew.toml
+//! or else javaup's default, and installs a pinned jdk through javaup when it is missing
+use std::io::{self, IsTerminal, Write};
+
+use javaup::{
+    config,
+    request_builder::RequestBuilder,
+    toolchain::{self, Toolchain},
+    ToolChain,
+};
+
+use crate::config::Config;
+
+#[derive(Debug, thiserror::Error)]
+pub enum ToolchainError {
+    #[error("no jdk to use, pin one with [toolchain] in Brew.toml or set a default with `javaup default`")]
+    NoToolchain,
+    #[error("{0}")]
+    Javaup(toolchain::ToolchainError),
+}
+
+/// The jdk of the brew, asking to install it when it is pinned in Brew.toml but javaup doesn't have it yet
+pub fn find(config: &Config) -> Result<Toolchain, ToolchainError> {
+    let Some(pinned) = config.toolchain() else {
+        return Toolchain::default_jdk().map_err(|e| match e {
+            toolchain::ToolchainError::NoDefault => ToolchainError::NoToolchain,
+            e => ToolchainError::Javaup(e),
+        });
+    };
+    let pinned = ToolChain {
+        version: pinned.version().to_string(),
+        distribution: pinned.distribution().to_string(),
+    };
+    match Toolchain::resolve(&pinned) {
+        Err(toolchain::ToolchainError::NotInstalled(_)) if confirm_install(&pinned) => {
+            config::unless_exists(&config::root_dir(), config::init);
+            RequestBuilder::new().install(pinned.clone()).execute();
+            Toolchain::resolve(&pinned).map_err(ToolchainError::Javaup)
+        }
+        toolchain => toolchain.map_err(ToolchainError::Javaup),
+    }
+}
+
+/// `--release` with the jdk's java version, which keeps javac and javadoc to the api of that version,
+/// jdk 8 doesn't know the flag and has nothing but its own api anyway
+pub fn release_args(jdk: &Toolchain) -> Vec<String> {
+    let feature = jdk.release().feature();
+    if feature >= 9 {
+        vec!["--release".to_string(), feature.to_string()]
+    } else {
+        vec![]
+    }
+}
+
+fn confirm_install(toolchain: &ToolChain) -> bool {
+    // there is no one to ask in ci or when piped
+    if !io::stdin().is_terminal() {
+        return false;
+    }
+    print!(
+        "jdk {} {} is not installed, install it with javaup? [Y/n] ",
+        toolchain.distribution, toolchain.version
+    );
+    io::stdout().flush().ok();
+    let mut answer = String::new();
+    if io::stdin().read_line(&mut answer).is_err() {
+        return false;
+    }
+    matches!(answer.trim().to_lowercase().as_str(), "" | "y" | "yes")
+}
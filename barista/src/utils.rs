@@ -1,7 +1,7 @@
 use std::{
-    fs::File,
+    fs::{self, File},
     io::{self, Read},
-    path::{Path, PathBuf},
+    path::{Component, Path, PathBuf},
 };
 
 use serde::Deserialize;
@@ -37,29 +37,6 @@ pub fn find_file(file_name: &str) -> Result<PathBuf, FindFileError> {
         })
         .ok_or(FindFileError::FileNotFound(file_name.to_string()))
 }
-/// Tries to find a config file in the current directory or any of its ancestors
-pub fn find_file_in_dir(file_name: &str) -> Result<PathBuf, FindFileError> {
-    std::env::current_dir()
-        .map_err(FindFileError::IO)?
-        .ancestors()
-        .find_map(|dir| {
-            if let Ok(mut dir) = dir.read_dir() {
-                dir.find_map(|file| {
-                    if let Ok(file) = file {
-                        if let Ok(file_type) = file.file_type() {
-                            if file_type.is_file() && file.path().ends_with(file_name) {
-                                return Some(file.path());
-                            }
-                        }
-                    }
-                    None
-                })
-            } else {
-                None
-            }
-        })
-        .ok_or(FindFileError::FileNotFound(file_name.to_string()))
-}
 #[derive(Debug, thiserror::Error)]
 pub enum TomlOpenError {
     #[error("error could not open file: {0}, path: {1}")]
@@ -84,3 +61,103 @@ pub fn unless_exists(path: impl Into<PathBuf>, f: impl Fn()) {
         f()
     }
 }
+
+/// Lists every file under dir (recursively), an unreadable or missing dir has no files
+pub fn walk_dir(dir: &Path) -> Vec<PathBuf> {
+    fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .filter_map(Result::ok)
+                .flat_map(|entry| {
+                    let path = entry.path();
+                    if path.is_dir() {
+                        walk_dir(&path)
+                    } else {
+                        vec![path]
+                    }
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// The path of `to` relative to the directory `from`, ie ../lib from a brew's jar directory
+pub fn relative_path(from: &Path, to: &Path) -> PathBuf {
+    let common = from
+        .components()
+        .zip(to.components())
+        .take_while(|(from, to)| from == to)
+        .count();
+    from.components()
+        .skip(common)
+        .map(|_| Component::ParentDir)
+        .chain(to.components().skip(common))
+        .collect()
+}
+
+/// Removes `//` and `/* */` comments from java source, so declarations can be found by splitting on whitespace
+pub fn strip_comments(source: &str) -> String {
+    let mut out = String::with_capacity(source.len());
+    let mut rest = source;
+    while !rest.is_empty() {
+        if let Some(comment) = rest.strip_prefix("//") {
+            rest = comment.find('\n').map_or("", |end| &comment[end..]);
+        } else if let Some(comment) = rest.strip_prefix("/*") {
+            rest = comment.find("*/").map_or("", |end| &comment[end + 2..]);
+            out.push(' ');
+        } else {
+            let c = rest.chars().next().unwrap();
+            out.push(c);
+            rest = &rest[c.len_utf8()..];
+        }
+    }
+    out
+}
+
+/// The package a java source file declares, None for the default package
+pub fn declared_package(source: &str) -> Option<String> {
+    let source = strip_comments(source);
+    let mut tokens = source
+        .split(|c: char| c.is_whitespace() || c == ';')
+        .filter(|token| !token.is_empty());
+    // the package declaration has to come before anything else
+    (tokens.next()? == "package")
+        .then(|| tokens.next().map(str::to_string))
+        .flatten()
+}
+
+/// Whether a java source file declares a main method (static or, since java 21, an instance one)
+pub fn has_main(source: &str) -> bool {
+    let source = strip_comments(source);
+    let tokens = source
+        .split(|c: char| c.is_whitespace() || c == '(')
+        .filter(|token| !token.is_empty())
+        .collect::<Vec<_>>();
+    tokens
+        .windows(2)
+        .any(|pair| pair[0] == "void" && pair[1] == "main")
+}
+
+/// The UTC date and time of seconds since the unix epoch as (year, month, day, hour, minute, second)
+pub fn civil_time(epoch: i64) -> (i64, u8, u8, u8, u8, u8) {
+    let days = epoch.div_euclid(86400);
+    let seconds = epoch.rem_euclid(86400);
+    // days to civil date from http://howardhinnant.github.io/date_algorithms.html
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (
+        year,
+        month as u8,
+        day as u8,
+        (seconds / 3600) as u8,
+        (seconds % 3600 / 60) as u8,
+        (seconds % 60) as u8,
+    )
+}
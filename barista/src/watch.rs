@@ -0,0 +1,210 @@
+//! watch.rs reruns roast, brew or sip whenever the sources, resources or Brew.toml of a brew change
+use std::{
+    panic::{self, AssertUnwindSafe},
+    path::{Path, PathBuf},
+    process::Child,
+    sync::mpsc::{self, Receiver, RecvTimeoutError},
+    thread,
+    time::{Duration, Instant},
+};
+
+use notify::{Event, EventKind, RecursiveMode, Watcher};
+
+use crate::{
+    brew::{java_command, DebugOptions},
+    config::{Config, OpenConfigError, ResourcesConfig},
+    diagnostics::MessageFormat,
+    roast::{roast, RoastOptions},
+    sip::sip,
+};
+
+/// How long to wait for more changes before rebuilding, editors often write a file in several steps
+const DEBOUNCE: Duration = Duration::from_millis(300);
+
+/// How long a brew being restarted gets to run its shutdown hooks before it is killed
+const STOP_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Default)]
+pub enum WatchCommand {
+    /// rebuild and restart the brew
+    #[default]
+    Brew,
+    /// only rebuild
+    Roast,
+    /// rebuild and run the tests
+    Sip,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum WatchError {
+    #[error("{0}")]
+    Config(OpenConfigError),
+    #[error("error watching for changes: {0}")]
+    Notify(notify::Error),
+    #[error("stopped receiving changes")]
+    Disconnected,
+}
+
+pub fn watch(
+    bin: Option<String>,
+    command: WatchCommand,
+    args: Vec<String>,
+) -> Result<(), WatchError> {
+    let root = crate::config::get_root_path().unwrap();
+    let config = Config::find_and_open_config().map_err(WatchError::Config)?;
+    let default_resources = ResourcesConfig::default();
+    let resources = root.join(config.resources().unwrap_or(&default_resources).path());
+
+    let (sender, receiver) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(sender).map_err(WatchError::Notify)?;
+    // the root itself is only watched for Brew.toml, as editors often replace it instead of writing to it
+    watcher
+        .watch(&root, RecursiveMode::NonRecursive)
+        .map_err(WatchError::Notify)?;
+    // the source directories of the default features are watched too
+    let mut sources = config.source_roots(&root, config.brew().source());
+    sources.retain(|source| source.exists());
+    if !resources.starts_with(config.source_root(&root)) && resources.exists() {
+        sources.push(resources);
+    }
+    for source in &sources {
+        watcher
+            .watch(source, RecursiveMode::Recursive)
+            .map_err(WatchError::Notify)?;
+    }
+    let config_path = root.join("Brew.toml");
+
+    let mut running: Option<Child> = None;
+    let mut fetch = true;
+    loop {
+        if let Some(mut child) = running.take() {
+            // the old program has to be gone before bin is rebuilt under it
+            stop(&mut child);
+        }
+        running = run(bin.clone(), command, args.clone(), !fetch);
+        println!("watching for changes...");
+        fetch = wait_for_changes(&receiver, &config_path, &sources, &mut running)?;
+    }
+}
+
+/// Asks the brew to stop (SIGTERM, or taskkill without /F on windows) so its shutdown hooks and finally
+/// blocks run, and kills it if it is still running after [`STOP_TIMEOUT`]
+fn stop(child: &mut Child) {
+    if !matches!(child.try_wait(), Ok(None)) {
+        return;
+    }
+    #[cfg(unix)]
+    // SAFETY: kill only sends a signal, to a child that hasn't been waited on so its pid isn't reused yet
+    unsafe {
+        libc::kill(child.id() as libc::pid_t, libc::SIGTERM);
+    }
+    #[cfg(windows)]
+    std::process::Command::new("taskkill")
+        .arg("/PID")
+        .arg(child.id().to_string())
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .status()
+        .ok();
+    let start = Instant::now();
+    while start.elapsed() < STOP_TIMEOUT {
+        match child.try_wait() {
+            Ok(None) => thread::sleep(Duration::from_millis(50)),
+            _ => return,
+        }
+    }
+    child.kill().ok();
+    child.wait().ok();
+}
+
+/// Runs the command once, a panic (ie a blend that fails to fetch) is reported and waits for the next change
+fn run(
+    bin: Option<String>,
+    command: WatchCommand,
+    args: Vec<String>,
+    skip_fetch: bool,
+) -> Option<Child> {
+    panic::catch_unwind(AssertUnwindSafe(|| {
+        let options = RoastOptions {
+            skip_fetch,
+            ..Default::default()
+        };
+        match command {
+            WatchCommand::Roast => {
+                if let Err(e) = roast(bin, options) {
+                    eprintln!("{e}");
+                }
+                None
+            }
+            WatchCommand::Brew => {
+                // there is nothing to restart until the brew compiles again
+                if let Err(e) = roast(bin.clone(), options) {
+                    eprintln!("{e}");
+                    return None;
+                }
+                let mut java = java_command(bin, args, DebugOptions::default())
+                    .map_err(|e| eprintln!("{e}"))
+                    .ok()?;
+                java.spawn()
+                    .map_err(|e| eprintln!("could not start the brew: {e}"))
+                    .ok()
+            }
+            WatchCommand::Sip => {
+                match sip(
+                    None,
+                    DebugOptions::default(),
+                    skip_fetch,
+                    MessageFormat::Human,
+                ) {
+                    Ok(code) => println!("tests finished with exit code {code}"),
+                    Err(e) => eprintln!("{e}"),
+                }
+                None
+            }
+        }
+    }))
+    .ok()
+    .flatten()
+}
+
+/// Blocks until something changed and no more changes came in for [`DEBOUNCE`],
+/// returning whether Brew.toml was one of the changes (so the blends have to be fetched again)
+fn wait_for_changes(
+    receiver: &Receiver<notify::Result<Event>>,
+    config_path: &Path,
+    sources: &[PathBuf],
+    running: &mut Option<Child>,
+) -> Result<bool, WatchError> {
+    let mut config_changed = false;
+    let mut changed = false;
+    loop {
+        let timeout = if changed {
+            DEBOUNCE
+        } else {
+            Duration::from_millis(100)
+        };
+        match receiver.recv_timeout(timeout) {
+            Ok(Ok(event)) if !matches!(event.kind, EventKind::Access(_)) => {
+                for path in event.paths {
+                    if path == config_path {
+                        config_changed = true;
+                        changed = true;
+                    } else if sources.iter().any(|source| path.starts_with(source)) {
+                        changed = true;
+                    }
+                }
+            }
+            Ok(Ok(_)) => {}
+            Ok(Err(e)) => eprintln!("error watching for changes: {e}"),
+            Err(RecvTimeoutError::Timeout) if changed => return Ok(config_changed),
+            Err(RecvTimeoutError::Timeout) => {
+                // let the user know when the program is done on its own
+                if let Some(Ok(Some(status))) = running.as_mut().map(Child::try_wait) {
+                    println!("brew exited with {status}");
+                    *running = None;
+                }
+            }
+            Err(RecvTimeoutError::Disconnected) => return Err(WatchError::Disconnected),
+        }
+    }
+}
@@ -0,0 +1,202 @@
+//! workspace.rs finds the workspace a brew belongs to, a Brew.toml with a `[workspace]` section listing
+//! member brews that share one Brew.lock and lib, and the blends the members declare once for all of them
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    config::{BlendConfig, Config, OpenConfigError},
+    utils::open_toml,
+};
+
+#[derive(Debug, thiserror::Error)]
+pub enum WorkspaceError {
+    #[error("not in a workspace, --workspace and -p need a Brew.toml with a [workspace] section")]
+    NotInWorkspace,
+    #[error("could not open workspace member {1}: {0}")]
+    Member(Box<OpenConfigError>, String),
+    #[error("no workspace member named {0}, members: {1}")]
+    NoMember(String, String),
+    #[error("workspace members use each other as blends in a cycle: {0}")]
+    Cycle(String),
+    #[error("blend {0} uses workspace = true but the brew is not in a workspace")]
+    NoWorkspace(String),
+    #[error("blend {0} uses workspace = true but is not in [workspace.blends]")]
+    NoWorkspaceBlend(String),
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+struct WorkspaceManifest {
+    workspace: WorkspaceConfig,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct WorkspaceConfig {
+    /// the directories of the member brews relative to the workspace
+    members: Vec<String>,
+    /// blends members can use with `workspace = true`, so their versions are declared once
+    #[serde(default)]
+    blends: HashMap<String, BlendConfig>,
+}
+
+pub struct Workspace {
+    root: PathBuf,
+    config: WorkspaceConfig,
+}
+
+impl Workspace {
+    /// The workspace of the brew the current directory is in
+    pub fn find() -> Option<Self> {
+        Self::containing(&crate::config::get_root_path().ok()?)
+    }
+
+    /// The closest workspace above (or at) `brew_root` that has the brew as a member or is the brew itself
+    pub fn containing(brew_root: &Path) -> Option<Self> {
+        brew_root.ancestors().find_map(|dir| {
+            let manifest: WorkspaceManifest = open_toml(&dir.join("Brew.toml")).ok()?;
+            let workspace = Self {
+                root: dir.to_path_buf(),
+                config: manifest.workspace,
+            };
+            (dir == brew_root
+                || workspace
+                    .members()
+                    .iter()
+                    .any(|member| same_dir(member, brew_root)))
+            .then_some(workspace)
+        })
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    pub fn members(&self) -> Vec<PathBuf> {
+        self.config
+            .members
+            .iter()
+            .map(|member| self.root.join(member))
+            .collect()
+    }
+
+    /// Every member with its Brew.toml
+    pub fn member_configs(&self) -> Result<Vec<(PathBuf, Config)>, WorkspaceError> {
+        self.members()
+            .into_iter()
+            .map(|member| {
+                Config::open_in_workspace(&member.join("Brew.toml"))
+                    .map(|config| (member.clone(), config))
+                    .map_err(|e| WorkspaceError::Member(Box::new(e), member.display().to_string()))
+            })
+            .collect()
+    }
+
+    /// A blend declared in `[workspace.blends]`, with its path (if any) relative to the workspace
+    pub fn blend(&self, name: &str) -> Option<BlendConfig> {
+        self.config
+            .blends
+            .get(name)
+            .map(|blend| blend.clone().relative_to(&self.root))
+    }
+}
+
+/// The members a command runs for, in build order, `None` when it runs for the brew it is run in
+pub fn select_members(
+    all: bool,
+    packages: &[String],
+) -> Result<Option<Vec<PathBuf>>, WorkspaceError> {
+    let Some(workspace) = Workspace::find() else {
+        if all || !packages.is_empty() {
+            return Err(WorkspaceError::NotInWorkspace);
+        }
+        return Ok(None);
+    };
+    let members = workspace.member_configs()?;
+    let selected = if all {
+        members
+    } else if packages.is_empty() {
+        // a workspace's own Brew.toml usually isn't a brew, so running there means running for every member
+        let in_root = crate::config::get_root_path().is_ok_and(|root| root == workspace.root);
+        if !in_root
+            || members
+                .iter()
+                .any(|(member, _)| same_dir(member, &workspace.root))
+        {
+            return Ok(None);
+        }
+        members
+    } else {
+        let names = members
+            .iter()
+            .map(|(_, config)| config.brew().name().to_string())
+            .collect::<Vec<_>>();
+        if let Some(unknown) = packages.iter().find(|package| !names.contains(package)) {
+            return Err(WorkspaceError::NoMember(unknown.clone(), names.join(", ")));
+        }
+        members
+            .into_iter()
+            .filter(|(_, config)| {
+                packages
+                    .iter()
+                    .any(|package| package == config.brew().name())
+            })
+            .collect()
+    };
+    build_order(&selected).map(Some)
+}
+
+/// Orders members so that every member comes after the members it uses as path blends
+fn build_order(members: &[(PathBuf, Config)]) -> Result<Vec<PathBuf>, WorkspaceError> {
+    fn visit(
+        index: usize,
+        members: &[(PathBuf, Config)],
+        visiting: &mut Vec<usize>,
+        order: &mut Vec<usize>,
+    ) -> Result<(), WorkspaceError> {
+        if order.contains(&index) {
+            return Ok(());
+        }
+        if let Some(start) = visiting.iter().position(|&visited| visited == index) {
+            let cycle = visiting[start..]
+                .iter()
+                .chain([&index])
+                .map(|&member| members[member].1.brew().name())
+                .collect::<Vec<_>>();
+            return Err(WorkspaceError::Cycle(cycle.join(" -> ")));
+        }
+        visiting.push(index);
+        let (root, config) = &members[index];
+        for path in config.blends().values().filter_map(BlendConfig::path) {
+            let blend_root = root.join(path);
+            if let Some(blend) = members
+                .iter()
+                .position(|(member, _)| same_dir(member, &blend_root))
+            {
+                visit(blend, members, visiting, order)?;
+            }
+        }
+        visiting.pop();
+        order.push(index);
+        Ok(())
+    }
+    let mut order = vec![];
+    for index in 0..members.len() {
+        visit(index, members, &mut vec![], &mut order)?;
+    }
+    Ok(order
+        .into_iter()
+        .map(|index| members[index].0.clone())
+        .collect())
+}
+
+/// Whether two paths are the same directory, members are often written as ../name
+fn same_dir(a: &Path, b: &Path) -> bool {
+    match (fs::canonicalize(a), fs::canonicalize(b)) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => a == b,
+    }
+}
@@ -0,0 +1,11 @@
+public class Main {
+    public static void main(String[] args) {
+        if (args.length == 0) {
+            System.err.println("usage: {{name}} <name>...");
+            System.exit(1);
+        }
+        for (String name : args) {
+            System.out.println("Hello, " + name + "!");
+        }
+    }
+}
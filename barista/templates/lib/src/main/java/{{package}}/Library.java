@@ -0,0 +1,7 @@
+package {{package}};
+
+public class Library {
+    public static String greet(String name) {
+        return "Hello, " + name + "!";
+    }
+}
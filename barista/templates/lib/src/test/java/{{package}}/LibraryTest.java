@@ -0,0 +1,12 @@
+package {{package}};
+
+import static org.junit.Assert.assertEquals;
+
+import org.junit.Test;
+
+public class LibraryTest {
+    @Test
+    public void greets() {
+        assertEquals("Hello, World!", Library.greet("World"));
+    }
+}
@@ -3,6 +3,7 @@ use serde::{Deserialize, Serialize};
 
 pub mod config;
 pub mod request_builder;
+pub mod toolchain;
 const DEFALT_VERSION: &str = "17";
 const DEFALT_DISTRIBUTION: &str = "temurin";
 
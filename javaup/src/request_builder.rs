@@ -17,9 +17,11 @@ use tokio::runtime::{Builder, Runtime};
 
 const BASE_URL: &str = "https://api.foojay.io/disco/v3.0/";
 
+type Action = Box<dyn Fn(reqwest::RequestBuilder, &Runtime)>;
+
 pub struct RequestBuilder {
     url: String,
-    action: Box<dyn Fn(reqwest::RequestBuilder, &Runtime)>,
+    action: Action,
     runtime: Runtime,
 }
 impl Default for RequestBuilder {
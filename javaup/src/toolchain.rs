@@ -0,0 +1,177 @@
+//! toolchain.rs resolves an installed jdk to a handle with the paths of its tools,
+//! so tools building on javaup don't have to know how jdks are laid out
+use std::{
+    collections::HashMap,
+    env, fs, io,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    config::{config_file_path, jdkdir, Settings},
+    ToolChain,
+};
+
+#[derive(Debug, thiserror::Error)]
+pub enum ToolchainError {
+    #[error("no default jdk set, set one with `javaup default`")]
+    NoDefault,
+    #[error("error reading javaup settings {1}: {0}")]
+    Settings(String, String),
+    #[error("jdk {} {} is not installed, install it with `javaup install {} {}`", .0.distribution, .0.version, .0.version, .0.distribution)]
+    NotInstalled(ToolChain),
+    #[error("jdk in {1} is broken: {0}")]
+    Broken(String, String),
+}
+
+/// An installed jdk
+#[derive(Debug, Clone)]
+pub struct Toolchain {
+    toolchain: ToolChain,
+    home: PathBuf,
+    release: Release,
+}
+
+/// What a jdk's `release` file says about it
+#[derive(Debug, Clone)]
+pub struct Release {
+    java_version: String,
+    feature: u32,
+    properties: HashMap<String, String>,
+}
+
+impl Toolchain {
+    /// The installed jdk of the given version and distribution
+    pub fn resolve(toolchain: &ToolChain) -> Result<Self, ToolchainError> {
+        let home = jdkdir()
+            .join(&toolchain.distribution)
+            .join(&toolchain.version);
+        if !home.exists() {
+            return Err(ToolchainError::NotInstalled(toolchain.clone()));
+        }
+        let location = home.display().to_string();
+        let broken = |reason: String| ToolchainError::Broken(reason, location.clone());
+        let release = Release::read(&home.join("release"))
+            .map_err(|e| broken(format!("could not read its release file: {e}")))?
+            .ok_or_else(|| broken("its release file has no JAVA_VERSION".to_string()))?;
+        let toolchain = Self {
+            toolchain: toolchain.clone(),
+            home,
+            release,
+        };
+        let java = toolchain.java();
+        if !java.exists() {
+            return Err(broken(format!("{} is missing", java.display())));
+        }
+        Ok(toolchain)
+    }
+
+    /// The jdk set with `javaup default`
+    pub fn default_jdk() -> Result<Self, ToolchainError> {
+        let path = config_file_path();
+        if !path.exists() {
+            return Err(ToolchainError::NoDefault);
+        }
+        let settings_error = |e: String| ToolchainError::Settings(e, path.display().to_string());
+        let settings: Settings = fs::read_to_string(&path)
+            .map_err(|e| settings_error(e.to_string()))
+            .and_then(|settings| {
+                toml::de::from_str(&settings).map_err(|e| settings_error(e.to_string()))
+            })?;
+        Self::resolve(&settings.default_jdk.ok_or(ToolchainError::NoDefault)?)
+    }
+
+    /// The version and distribution the jdk was resolved from
+    pub fn toolchain(&self) -> &ToolChain {
+        &self.toolchain
+    }
+
+    pub fn java_home(&self) -> &Path {
+        &self.home
+    }
+
+    pub fn release(&self) -> &Release {
+        &self.release
+    }
+
+    /// The directory with the jdk's executables
+    pub fn bin(&self) -> PathBuf {
+        self.home.join("bin")
+    }
+
+    /// Any of the jdk's executables by name, ie jdeps or jpackage
+    pub fn tool(&self, name: &str) -> PathBuf {
+        self.bin()
+            .join(format!("{name}{}", env::consts::EXE_SUFFIX))
+    }
+
+    pub fn java(&self) -> PathBuf {
+        self.tool("java")
+    }
+
+    pub fn javac(&self) -> PathBuf {
+        self.tool("javac")
+    }
+
+    pub fn jar(&self) -> PathBuf {
+        self.tool("jar")
+    }
+
+    pub fn javadoc(&self) -> PathBuf {
+        self.tool("javadoc")
+    }
+
+    pub fn jlink(&self) -> PathBuf {
+        self.tool("jlink")
+    }
+}
+
+impl Release {
+    /// Parses a `release` file (`KEY="value"` per line), `None` if it has no java version
+    fn read(path: &Path) -> io::Result<Option<Self>> {
+        let properties = fs::read_to_string(path)?
+            .lines()
+            .filter_map(|line| line.split_once('='))
+            .map(|(key, value)| {
+                (
+                    key.trim().to_string(),
+                    value.trim().trim_matches('"').to_string(),
+                )
+            })
+            .collect::<HashMap<_, _>>();
+        let Some(java_version) = properties.get("JAVA_VERSION").cloned() else {
+            return Ok(None);
+        };
+        // java 8 and older are 1.x
+        let mut parts = java_version.split(['.', '_', '-', '+']);
+        let feature = match parts.next() {
+            Some("1") => parts.next(),
+            first => first,
+        }
+        .and_then(|feature| feature.parse().ok());
+        Ok(feature.map(|feature| Self {
+            java_version,
+            feature,
+            properties,
+        }))
+    }
+
+    /// The full java version, ie 21.0.2
+    pub fn java_version(&self) -> &str {
+        &self.java_version
+    }
+
+    /// The feature release, ie 21 for 21.0.2 and 8 for 1.8.0_392, what javac's --release takes
+    pub fn feature(&self) -> u32 {
+        self.feature
+    }
+
+    /// Who built the jdk, ie Eclipse Adoptium
+    pub fn implementor(&self) -> Option<&str> {
+        self.property("IMPLEMENTOR")
+    }
+
+    /// Any other property of the release file, ie MODULES or OS_ARCH
+    pub fn property(&self, key: &str) -> Option<&str> {
+        self.properties.get(key).map(String::as_str)
+    }
+}